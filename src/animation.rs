@@ -0,0 +1,341 @@
+//! Animations only change how the board is drawn, the game itself moves on
+//! at once, so they never hold up input and can be skipped at any time.
+
+use std::{collections::HashMap, path::Path};
+
+use macroquad::{
+    color::{Color, ORANGE, WHITE},
+    math::Vec2,
+    shapes::draw_circle,
+    texture::{draw_texture_ex, DrawTextureParams, Texture2D},
+    time::get_frame_time,
+};
+
+use crate::{game_textures::GameTextures, tile::TileState, vector2::Vector2, vector3::Vector3};
+
+/// Seconds between a revealed tile and the ones one step further out.
+const RIPPLE_STEP: f32 = 0.02;
+/// Seconds between the explosions of mines one step apart, shortened on
+/// large boards so the whole chain stays under [`MAX_EXPLOSION_CHAIN`].
+const EXPLOSION_STEP: f32 = 0.08;
+const MAX_EXPLOSION_CHAIN: f32 = 2.0;
+const FIRE_DURATION: f32 = 0.6;
+const CELEBRATION_DURATION: f32 = 3.0;
+/// bullets each jet fires per second
+const FIRE_RATE: f32 = 6.0;
+
+#[derive(Debug, Clone)]
+struct AnimationTextures {
+    fire: Option<Texture2D>,
+    jet: Option<Texture2D>,
+    bullet: Option<Texture2D>,
+}
+
+#[derive(Debug, Clone)]
+struct Ripple {
+    /// seconds before each revealed tile shows, by index
+    delays: HashMap<usize, f32>,
+    elapsed: f32,
+}
+
+#[derive(Debug, Clone)]
+struct Explosion {
+    /// mines in the order they go off, with the seconds before they do
+    mines: Vec<(Vector3<i32>, f32)>,
+    /// the same seconds by index of the mine
+    delays: HashMap<usize, f32>,
+    elapsed: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Animations {
+    /// off without a window or when turned off in the settings
+    enabled: bool,
+    textures: Option<AnimationTextures>,
+    ripple: Option<Ripple>,
+    explosion: Option<Explosion>,
+    /// seconds since the win
+    celebration: Option<f32>,
+    /// tiles shown differently since [`Animations::take_changed`]
+    changed: Vec<usize>,
+}
+
+impl Animations {
+    pub async fn new(enabled: bool) -> Self {
+        let textures = AnimationTextures {
+            fire: GameTextures::load(Path::new("textures/fire.png")).await,
+            jet: GameTextures::load(Path::new("textures/fighter-jet.png")).await,
+            bullet: GameTextures::load(Path::new("textures/bullet.png")).await,
+        };
+
+        Animations {
+            enabled,
+            textures: Some(textures),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.skip();
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.ripple.is_some()
+            || self.celebration.is_some()
+            || self
+                .explosion
+                .as_ref()
+                .is_some_and(|explosion| explosion.elapsed < explosion.duration())
+    }
+
+    /// Forgets the animations of the previous game.
+    pub fn clear(&mut self) {
+        if let Some(ripple) = self.ripple.take() {
+            self.changed.extend(ripple.delays.into_keys());
+        }
+        if let Some(explosion) = self.explosion.take() {
+            self.changed.extend(explosion.delays.into_keys());
+        }
+        self.celebration = None;
+    }
+
+    /// Jumps to the end of every animation.
+    pub fn skip(&mut self) {
+        if let Some(ripple) = &self.ripple {
+            self.changed
+                .extend(passing(&ripple.delays, ripple.elapsed, f32::INFINITY));
+        }
+        if let Some(explosion) = &mut self.explosion {
+            self.changed
+                .extend(passing(&explosion.delays, explosion.elapsed, f32::INFINITY));
+            explosion.elapsed = f32::INFINITY;
+        }
+        self.ripple = None;
+        self.celebration = None;
+    }
+
+    /// The tiles shown differently since the last call, as the animations
+    /// moved on, started or ended.
+    pub fn take_changed(&mut self) -> Vec<usize> {
+        std::mem::take(&mut self.changed)
+    }
+
+    /// Shows newly revealed tiles one step further from the click at a time,
+    /// given their index and distance.
+    pub fn ripple(&mut self, tiles: Vec<(usize, i32)>) {
+        if !self.enabled {
+            return;
+        }
+
+        let delays: HashMap<usize, f32> = tiles
+            .into_iter()
+            .map(|(index, distance)| (index, distance as f32 * RIPPLE_STEP))
+            .collect();
+        self.changed.extend(delays.keys());
+        self.ripple = (delays.len() > 1).then_some(Ripple {
+            delays,
+            elapsed: 0.0,
+        });
+    }
+
+    /// Sets off the mines one after another, given their position, index and
+    /// distance from the one that was hit.
+    pub fn explode(&mut self, mut mines: Vec<(Vector3<i32>, usize, i32)>) {
+        mines.sort_by_key(|(_, _, distance)| *distance);
+        let furthest = mines.last().map_or(1, |(_, _, distance)| *distance).max(1);
+        let step = EXPLOSION_STEP.min(MAX_EXPLOSION_CHAIN / furthest as f32);
+
+        let delay = |distance: i32| distance as f32 * step;
+        self.changed
+            .extend(mines.iter().map(|(_, index, _)| *index));
+        self.explosion = Some(Explosion {
+            delays: mines
+                .iter()
+                .map(|(_, index, distance)| (*index, delay(*distance)))
+                .collect(),
+            mines: mines
+                .iter()
+                .map(|(pos, _, distance)| (*pos, delay(*distance)))
+                .collect(),
+            elapsed: match self.enabled {
+                true => 0.0,
+                false => f32::INFINITY,
+            },
+        });
+    }
+
+    pub fn celebrate(&mut self) {
+        if self.enabled {
+            self.celebration = Some(0.0);
+        }
+    }
+
+    /// Moves the animations on by the time of the last frame.
+    pub fn update(&mut self) {
+        let frame_time = get_frame_time();
+
+        if let Some(ripple) = &mut self.ripple {
+            let elapsed = ripple.elapsed + frame_time;
+            self.changed
+                .extend(passing(&ripple.delays, ripple.elapsed, elapsed));
+            ripple.elapsed = elapsed;
+            if ripple.delays.values().all(|delay| *delay <= ripple.elapsed) {
+                self.ripple = None;
+            }
+        }
+
+        if let Some(explosion) = &mut self.explosion {
+            let elapsed = explosion.elapsed + frame_time;
+            self.changed
+                .extend(passing(&explosion.delays, explosion.elapsed, elapsed));
+            explosion.elapsed = elapsed;
+        }
+
+        if let Some(elapsed) = &mut self.celebration {
+            *elapsed += frame_time;
+            if *elapsed > CELEBRATION_DURATION {
+                self.celebration = None;
+            }
+        }
+    }
+
+    /// State a tile is drawn in: revealed tiles wait for the ripple to reach
+    /// them, and mines show once they went off.
+    pub fn shown_state(&self, index: usize, state: TileState) -> TileState {
+        let waiting = self.ripple.as_ref().is_some_and(|ripple| {
+            ripple
+                .delays
+                .get(&index)
+                .is_some_and(|delay| *delay > ripple.elapsed)
+        });
+        if waiting {
+            return TileState::Hidden;
+        }
+
+        let exploded = self.explosion.as_ref().is_some_and(|explosion| {
+            explosion
+                .delays
+                .get(&index)
+                .is_some_and(|delay| *delay <= explosion.elapsed)
+        });
+        match exploded {
+            true => TileState::Revealed,
+            false => state,
+        }
+    }
+
+    /// Draws the fire over the mines going off, growing and fading out.
+    pub fn draw_explosions(&self, tile_pos: impl Fn(Vector3<i32>) -> Vector2<f32>, size: f32) {
+        let Some(explosion) = &self.explosion else {
+            return;
+        };
+        let fire = self
+            .textures
+            .as_ref()
+            .and_then(|textures| textures.fire.as_ref());
+
+        for (pos, delay) in &explosion.mines {
+            let progress = (explosion.elapsed - delay) / FIRE_DURATION;
+            if !(0.0..1.0).contains(&progress) {
+                continue;
+            }
+
+            let center = tile_pos(*pos).add_val(size / 2.0);
+            let fire_size = size * (0.6 + progress);
+            let alpha = (2.0 - progress * 2.0).min(1.0);
+
+            match fire {
+                Some(texture) => draw_texture_ex(
+                    texture,
+                    center.x - fire_size / 2.0,
+                    center.y - fire_size / 2.0,
+                    Color::new(1.0, 1.0, 1.0, alpha),
+                    DrawTextureParams {
+                        dest_size: Some(Vec2::splat(fire_size)),
+                        ..Default::default()
+                    },
+                ),
+                None => draw_circle(
+                    center.x,
+                    center.y,
+                    fire_size / 2.0,
+                    Color { a: alpha, ..ORANGE },
+                ),
+            }
+        }
+    }
+
+    /// Flies three jets in formation up across the board, firing ahead.
+    pub fn draw_celebration(&self, pos: Vector2<f32>, size: Vector2<f32>) {
+        let (Some(elapsed), Some(textures)) = (self.celebration, &self.textures) else {
+            return;
+        };
+        let Some(jet) = &textures.jet else {
+            return;
+        };
+
+        let jet_size = size.y / 4.0;
+        let progress = elapsed / CELEBRATION_DURATION;
+        let lead_y = pos.y + size.y + jet_size - progress * (size.y + jet_size * 3.0);
+        let center_x = pos.x + size.x / 2.0;
+
+        let formation = [(0.0, 0.0), (-1.2, 0.6), (1.2, 0.6)];
+        for (dx, dy) in formation {
+            let x = center_x + dx * jet_size - jet_size / 2.0;
+            let y = lead_y + dy * jet_size;
+
+            if let Some(bullet) = &textures.bullet {
+                let bullet_size = jet_size / 4.0;
+                let spacing = size.y / FIRE_RATE / 2.0;
+                let shift = (elapsed * FIRE_RATE).fract() * spacing;
+                (0..4).for_each(|i| {
+                    let bullet_y = y - bullet_size - i as f32 * spacing - shift;
+                    draw_texture_ex(
+                        bullet,
+                        x + (jet_size - bullet_size) / 2.0,
+                        bullet_y,
+                        WHITE,
+                        DrawTextureParams {
+                            dest_size: Some(Vec2::splat(bullet_size)),
+                            ..Default::default()
+                        },
+                    );
+                });
+            }
+
+            draw_texture_ex(
+                jet,
+                x,
+                y,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(Vec2::splat(jet_size)),
+                    ..Default::default()
+                },
+            );
+        }
+    }
+}
+
+/// The tiles whose delay is over by `to` but wasn't at `from`.
+fn passing(delays: &HashMap<usize, f32>, from: f32, to: f32) -> impl Iterator<Item = usize> + '_ {
+    delays
+        .iter()
+        .filter(move |(_, delay)| **delay > from && **delay <= to)
+        .map(|(index, _)| *index)
+}
+
+impl Explosion {
+    fn duration(&self) -> f32 {
+        self.mines
+            .last()
+            .map_or(0.0, |(_, delay)| delay + FIRE_DURATION)
+    }
+}
@@ -0,0 +1,141 @@
+//! Headless benchmark of the solver bot on seeded games of every level in
+//! `config.json`.
+//!
+//! ```bash
+//! cargo run --release --bin bench -- --games 1000 --seed 0 --level Expert
+//! ```
+
+use std::{env, time::Instant};
+
+use minesweeper::{
+    bot::{play, Bot, Solver},
+    config::{Config, GameLevelConfig},
+    game::Game,
+    utils::parse_args,
+};
+
+const DEFAULT_GAMES: u64 = 1000;
+/// Failure seeds listed per level.
+const DEFAULT_FAILURES: usize = 10;
+
+struct LevelReport {
+    games: u64,
+    wins: u64,
+    guesses: u64,
+    /// time the bot played, board generation left out
+    millis: f64,
+    failures: Vec<u64>,
+}
+
+fn main() {
+    let (options, _) = parse_args(env::args().skip(1));
+    let option = |name: &str, default: u64| {
+        options
+            .get(name)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(default)
+    };
+    let games = option("games", DEFAULT_GAMES).max(1);
+    let first_seed = option("seed", 0);
+    let shown_failures = option("failures", DEFAULT_FAILURES as u64) as usize;
+
+    let config = Config::new();
+    let mut bot = Solver::new();
+    let mut game = Game::headless();
+
+    println!(
+        "{} games per level with the {} bot, seeds from {}",
+        games,
+        bot.name(),
+        first_seed
+    );
+
+    for level in &config.levels {
+        if options
+            .get("level")
+            .is_some_and(|name| !level.name.eq_ignore_ascii_case(name))
+        {
+            continue;
+        }
+
+        if level.board.is_some() {
+            println!(
+                "\n{}: skipped, the board is not generated from a seed",
+                level.name
+            );
+            continue;
+        }
+
+        let report = run_level(&mut bot, &mut game, level, first_seed, games);
+        print_report(level, &report, shown_failures);
+    }
+}
+
+fn run_level(
+    bot: &mut dyn Bot,
+    game: &mut Game,
+    level: &GameLevelConfig,
+    first_seed: u64,
+    games: u64,
+) -> LevelReport {
+    let mut report = LevelReport {
+        games,
+        wins: 0,
+        guesses: 0,
+        millis: 0.0,
+        failures: Vec::new(),
+    };
+
+    for seed in first_seed..first_seed + games {
+        // only generated levels are played, which always start
+        let _ = game.start_level_with_seed(level, seed);
+        // the time of the bot alone, without generating and rating the board
+        let started = Instant::now();
+        let result = play(bot, game);
+
+        report.millis += started.elapsed().as_secs_f64() * 1000.0;
+        report.guesses += result.guesses as u64;
+        match result.won {
+            true => report.wins += 1,
+            false => report.failures.push(seed),
+        }
+    }
+
+    report
+}
+
+fn print_report(level: &GameLevelConfig, report: &LevelReport, shown_failures: usize) {
+    let games = report.games as f64;
+
+    println!(
+        "\n{} ({}x{}x{}, {} mines)",
+        level.name, level.rows, level.cols, level.layers, level.mines
+    );
+    println!(
+        "  win rate:      {:.1}% ({}/{})",
+        report.wins as f64 * 100.0 / games,
+        report.wins,
+        report.games
+    );
+    println!("  average time:  {:.3} ms", report.millis / games);
+    println!("  guesses/game:  {:.2}", report.guesses as f64 / games);
+
+    if !report.failures.is_empty() {
+        let seeds: Vec<String> = report
+            .failures
+            .iter()
+            .take(shown_failures)
+            .map(u64::to_string)
+            .collect();
+        let more = report.failures.len().saturating_sub(shown_failures);
+
+        println!(
+            "  failure seeds: {}{}",
+            seeds.join(", "),
+            match more {
+                0 => String::new(),
+                _ => format!(" and {} more", more),
+            }
+        );
+    }
+}
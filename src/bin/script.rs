@@ -0,0 +1,308 @@
+//! Plays the game through JSON lines on stdin and answers every command with
+//! the visible board on stdout, for programs driving the game as a
+//! subprocess.
+//!
+//! ```bash
+//! echo '{"command":"new","level":"Beginner","seed":7}
+//! {"command":"reveal","row":4,"col":4}' | cargo run -q --bin script
+//! ```
+
+use std::io::{self, BufRead, Write};
+
+use minesweeper::{
+    bot::{VisibleBoard, VisibleTile},
+    config::{Config, GameLevelConfig},
+    difficulty::Difficulty,
+    game::{Game, GameState},
+    vector3::Vector3,
+};
+use serde::{Deserialize, Serialize};
+
+/// The most tiles a custom board may have, every tile is answered on each line.
+const MAX_TILES: i32 = 1_000_000;
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+enum Command {
+    /// starts a level of `config.json` by name, or a board of the given size
+    New {
+        level: Option<String>,
+        rows: Option<i32>,
+        cols: Option<i32>,
+        layers: Option<i32>,
+        mines: Option<i32>,
+        seed: Option<u64>,
+    },
+    Reveal(Cell),
+    /// toggles the flag of a hidden tile
+    Flag(Cell),
+    /// reveals the neighbours of a revealed number whose mines are flagged
+    Chord(Cell),
+    State,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct Cell {
+    row: i32,
+    col: i32,
+    #[serde(default)]
+    layer: i32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum Response {
+    Ok(BoardState),
+    Error { message: String },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Outcome {
+    NotStarted,
+    Playing,
+    Won,
+    Lost,
+}
+
+#[derive(Debug, Serialize)]
+struct BoardState {
+    outcome: Outcome,
+    seed: u64,
+    rows: i32,
+    cols: i32,
+    layers: i32,
+    mines: i32,
+    mines_left: i32,
+    /// rating of generated boards
+    difficulty: Option<Difficulty>,
+    /// rows of every layer: `.` hidden, `F` flagged, `X` revealed mine,
+    /// `0-9` revealed and `+` for more than 9 mines around
+    board: Vec<Vec<String>>,
+}
+
+fn main() {
+    let config = Config::new();
+    let mut game = Game::headless();
+    let mut stdout = io::stdout();
+
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str(&line) {
+            Ok(command) => match run(&config, &mut game, command) {
+                Ok(()) => Response::Ok(board_state(&game)),
+                Err(message) => Response::Error { message },
+            },
+            Err(err) => Response::Error {
+                message: format!("invalid command: {}", err),
+            },
+        };
+
+        let written = serde_json::to_writer(&mut stdout, &response)
+            .map_err(io::Error::from)
+            .and_then(|_| writeln!(stdout))
+            .and_then(|_| stdout.flush());
+        if written.is_err() {
+            break;
+        }
+    }
+}
+
+fn run(config: &Config, game: &mut Game, command: Command) -> Result<(), String> {
+    match command {
+        Command::New {
+            level,
+            rows,
+            cols,
+            layers,
+            mines,
+            seed,
+        } => {
+            let level = new_level(config, level, rows, cols, layers, mines)?;
+            game.start_level_with_seed(&level, seed.unwrap_or_else(rand::random))
+                .map_err(|err| err.to_string())
+        }
+        Command::Reveal(cell) => {
+            let pos = playing_cell(game, cell)?;
+            match game.visible_board().get(pos) {
+                VisibleTile::Hidden => game.reveal(pos),
+                VisibleTile::Flagged => return Err("the tile is flagged".to_string()),
+                _ => return Err("the tile is already revealed".to_string()),
+            }
+            Ok(())
+        }
+        Command::Flag(cell) => {
+            let pos = playing_cell(game, cell)?;
+            match game.visible_board().get(pos) {
+                VisibleTile::Hidden | VisibleTile::Flagged => game.toggle_flag(pos),
+                _ => return Err("only hidden tiles can be flagged".to_string()),
+            }
+            Ok(())
+        }
+        Command::Chord(cell) => {
+            let pos = playing_cell(game, cell)?;
+            match game.visible_board().get(pos) {
+                VisibleTile::Number(_) => game.reveal(pos),
+                _ => return Err("only revealed numbers can be chorded".to_string()),
+            }
+            Ok(())
+        }
+        Command::State => Ok(()),
+    }
+}
+
+fn new_level(
+    config: &Config,
+    name: Option<String>,
+    rows: Option<i32>,
+    cols: Option<i32>,
+    layers: Option<i32>,
+    mines: Option<i32>,
+) -> Result<GameLevelConfig, String> {
+    if let Some(name) = name {
+        return config
+            .levels
+            .iter()
+            .find(|level| level.name.eq_ignore_ascii_case(&name))
+            .cloned()
+            .ok_or(format!("unknown level '{}'", name));
+    }
+
+    let (rows, cols, mines) = match (rows, cols, mines) {
+        (Some(rows), Some(cols), Some(mines)) => (rows, cols, mines),
+        (None, None, None) if layers.is_none() => return Ok(config.levels[0].clone()),
+        (None, None, None) => return Err("layers need rows, cols and mines".to_string()),
+        _ => return Err("rows, cols and mines go together".to_string()),
+    };
+
+    let layers = layers.unwrap_or(1);
+    if rows < 1 || cols < 1 || layers < 1 {
+        return Err("the board needs a tile".to_string());
+    }
+    let tiles = rows
+        .checked_mul(cols)
+        .and_then(|tiles| tiles.checked_mul(layers))
+        .filter(|&tiles| tiles <= MAX_TILES)
+        .ok_or(format!("the board can have at most {} tiles", MAX_TILES))?;
+    if mines < 0 || mines >= tiles {
+        return Err("the board needs fewer mines than tiles".to_string());
+    }
+
+    Ok(GameLevelConfig {
+        name: "Custom".to_string(),
+        rows,
+        cols,
+        layers,
+        mines,
+        board: None,
+        difficulty: None,
+    })
+}
+
+fn playing_cell(game: &Game, cell: Cell) -> Result<Vector3<i32>, String> {
+    if game.get_state() != GameState::Playing {
+        return Err("no game is being played, send \"new\" first".to_string());
+    }
+
+    let pos = Vector3::new(cell.row, cell.col, cell.layer);
+    match game.visible_board().within_bounds(pos) {
+        true => Ok(pos),
+        false => Err("the tile is outside the board".to_string()),
+    }
+}
+
+fn board_state(game: &Game) -> BoardState {
+    let board: VisibleBoard = game.visible_board();
+    let Vector3 {
+        x: rows,
+        y: cols,
+        z: layers,
+    } = board.dimensions;
+
+    let flagged = board
+        .tiles
+        .iter()
+        .filter(|tile| **tile == VisibleTile::Flagged)
+        .count() as i32;
+
+    let rows_text = (0..layers)
+        .map(|layer| {
+            (0..rows)
+                .map(|row| {
+                    (0..cols)
+                        .map(|col| match board.get(Vector3::new(row, col, layer)) {
+                            VisibleTile::Hidden => '.',
+                            VisibleTile::Flagged => 'F',
+                            VisibleTile::Mine => 'X',
+                            VisibleTile::Number(number) => {
+                                char::from_digit(number as u32, 10).unwrap_or('+')
+                            }
+                        })
+                        .collect()
+                })
+                .collect()
+        })
+        .collect();
+
+    BoardState {
+        outcome: match game.get_state() {
+            GameState::NotStarted => Outcome::NotStarted,
+            GameState::Playing => Outcome::Playing,
+            GameState::GameWon => Outcome::Won,
+            GameState::GameOver => Outcome::Lost,
+        },
+        seed: game.seed(),
+        rows,
+        cols,
+        layers,
+        mines: board.mines,
+        mines_left: board.mines - flagged,
+        difficulty: game.difficulty(),
+        board: rows_text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_custom_board_needs_rows_cols_and_mines() {
+        let config = Config::new();
+        let level = |rows, cols, layers, mines| new_level(&config, None, rows, cols, layers, mines);
+
+        assert_eq!(
+            level(None, None, None, None).unwrap().name,
+            config.levels[0].name
+        );
+        assert_eq!(
+            level(Some(5), Some(6), None, Some(3)).unwrap().name,
+            "Custom"
+        );
+        for (rows, cols, mines) in [
+            (Some(5), None, None),
+            (None, Some(6), Some(3)),
+            (Some(5), Some(6), None),
+        ] {
+            assert_eq!(
+                level(rows, cols, None, mines).unwrap_err(),
+                "rows, cols and mines go together"
+            );
+        }
+        assert!(level(None, None, Some(2), None).is_err());
+
+        for (rows, cols, layers) in [(65536, 65536, 1), (2000, 2000, 2000), (1001, 1000, 1)] {
+            assert_eq!(
+                level(Some(rows), Some(cols), Some(layers), Some(1)).unwrap_err(),
+                "the board can have at most 1000000 tiles"
+            );
+        }
+        assert!(level(Some(1000), Some(1000), Some(1), Some(1)).is_ok());
+    }
+}
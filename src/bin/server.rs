@@ -0,0 +1,316 @@
+//! Server of online races. It waits for enough players, hands out the same
+//! seeded board to all of them and relays everyone's progress. Race times are
+//! measured here from the start of the race; the progress and the outcome are
+//! what the clients report, as the server never sees their moves.
+//!
+//! ```bash
+//! cargo run --bin server -- --addr 127.0.0.1:7878 --players 2
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    env,
+    io::{BufRead, BufReader},
+    net::{Shutdown, TcpListener, TcpStream},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Instant,
+};
+
+use minesweeper::{
+    config::Config,
+    online::{
+        write_message, ClientMessage, OpponentProgress, PlayerStatus, ServerMessage, StartInfo,
+        DEFAULT_SERVER,
+    },
+    utils::parse_args,
+};
+
+const DEFAULT_PLAYERS: usize = 2;
+
+enum Event {
+    Connected(u32, TcpStream),
+    Message(u32, ClientMessage),
+    Disconnected(u32),
+}
+
+struct Client {
+    /// messages for the thread writing to the client, so a slow client
+    /// doesn't hold up the others
+    outbox: Sender<ServerMessage>,
+    name: String,
+    /// waiting for the next race
+    joined: bool,
+    /// taking part in the current race
+    racing: bool,
+    percent: f32,
+    status: PlayerStatus,
+}
+
+struct Lobby {
+    clients: BTreeMap<u32, Client>,
+    players_per_race: usize,
+    start: StartInfo,
+    started_at: Instant,
+}
+
+fn main() {
+    let (options, _) = parse_args(env::args().skip(1));
+    let addr = options
+        .get("addr")
+        .cloned()
+        .unwrap_or(DEFAULT_SERVER.to_string());
+    let players_per_race = options
+        .get("players")
+        .and_then(|players| players.parse().ok())
+        .unwrap_or(DEFAULT_PLAYERS)
+        .max(1);
+
+    let level = Config::new().online;
+    let listener = TcpListener::bind(&addr).expect("Could not bind the server address");
+    println!(
+        "Listening on {}, races of {} players on {}x{} with {} mines",
+        addr, players_per_race, level.rows, level.cols, level.mines
+    );
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || accept_clients(listener, sender));
+
+    let mut lobby = Lobby {
+        clients: BTreeMap::new(),
+        players_per_race,
+        start: StartInfo {
+            seed: 0,
+            rows: level.rows,
+            cols: level.cols,
+            mines: level.mines,
+        },
+        started_at: Instant::now(),
+    };
+
+    for event in receiver {
+        lobby.handle(event);
+    }
+}
+
+fn accept_clients(listener: TcpListener, sender: Sender<Event>) {
+    for (id, stream) in (1..).zip(listener.incoming()) {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("Could not accept a client: {}", err);
+                continue;
+            }
+        };
+
+        let reader = match stream.try_clone() {
+            Ok(reader) => reader,
+            Err(err) => {
+                eprintln!("Could not read from a client: {}", err);
+                continue;
+            }
+        };
+
+        if sender.send(Event::Connected(id, stream)).is_err() {
+            return;
+        }
+
+        let sender = sender.clone();
+        thread::spawn(move || read_messages(id, reader, sender));
+    }
+}
+
+/// Writes the messages for a client until it leaves. A failed write closes
+/// the connection, which shows up as a disconnect on the reading side.
+fn write_messages(mut stream: TcpStream, messages: Receiver<ServerMessage>) {
+    for message in messages {
+        if let Err(err) = write_message(&mut stream, &message) {
+            eprintln!("Could not send to a client: {}", err);
+            let _ = stream.shutdown(Shutdown::Both);
+            return;
+        }
+    }
+}
+
+fn read_messages(id: u32, stream: TcpStream, sender: Sender<Event>) {
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+
+        match serde_json::from_str(&line) {
+            Ok(message) => {
+                if sender.send(Event::Message(id, message)).is_err() {
+                    return;
+                }
+            }
+            Err(err) => eprintln!("Ignoring a message from client {}: {}", id, err),
+        }
+    }
+
+    let _ = sender.send(Event::Disconnected(id));
+}
+
+impl Lobby {
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Connected(id, stream) => {
+                let (outbox, messages) = mpsc::channel();
+                thread::spawn(move || write_messages(stream, messages));
+                self.clients.insert(
+                    id,
+                    Client {
+                        outbox,
+                        name: format!("Player {}", id),
+                        joined: false,
+                        racing: false,
+                        percent: 0.0,
+                        status: PlayerStatus::Playing,
+                    },
+                );
+            }
+            Event::Message(id, message) => self.handle_message(id, message),
+            Event::Disconnected(id) => {
+                if let Some(client) = self.clients.get_mut(&id) {
+                    println!("{} disconnected", client.name);
+                    client.status = PlayerStatus::Disconnected;
+                    self.broadcast_progress(id);
+                }
+                self.clients.remove(&id);
+                self.finish_race_if_done();
+            }
+        }
+    }
+
+    fn handle_message(&mut self, id: u32, message: ClientMessage) {
+        let Some(client) = self.clients.get_mut(&id) else {
+            return;
+        };
+
+        match message {
+            ClientMessage::Join { name } => {
+                println!("{} joined", name);
+                client.name = name;
+                client.joined = true;
+
+                let waiting_for = self.players_per_race.saturating_sub(self.joined_count());
+                self.send(
+                    id,
+                    &ServerMessage::Welcome {
+                        player_id: id,
+                        waiting_for,
+                    },
+                );
+                self.start_race_if_ready();
+            }
+            ClientMessage::Progress { percent } if client.racing => {
+                client.percent = percent.clamp(0.0, 100.0);
+                self.broadcast_progress(id);
+            }
+            // the client's own time is ignored for the server's
+            ClientMessage::Finished { .. } if client.racing => {
+                let seconds = self.started_at.elapsed().as_secs() as i64;
+                client.percent = 100.0;
+                client.status = PlayerStatus::Finished { seconds };
+                self.broadcast_progress(id);
+                self.finish_race_if_done();
+            }
+            ClientMessage::Lost if client.racing => {
+                client.status = PlayerStatus::Lost;
+                self.broadcast_progress(id);
+                self.finish_race_if_done();
+            }
+            _ => {}
+        }
+    }
+
+    fn joined_count(&self) -> usize {
+        self.clients.values().filter(|client| client.joined).count()
+    }
+
+    fn is_racing(&self) -> bool {
+        self.clients.values().any(|client| client.racing)
+    }
+
+    fn start_race_if_ready(&mut self) {
+        if self.is_racing() || self.joined_count() < self.players_per_race {
+            return;
+        }
+
+        self.start.seed = rand::random();
+        self.started_at = Instant::now();
+        let racers: Vec<u32> = self
+            .clients
+            .iter()
+            .filter(|(_, client)| client.joined)
+            .map(|(id, _)| *id)
+            .take(self.players_per_race)
+            .collect();
+
+        for id in &racers {
+            if let Some(client) = self.clients.get_mut(id) {
+                client.joined = false;
+                client.racing = true;
+                client.percent = 0.0;
+                client.status = PlayerStatus::Playing;
+            }
+        }
+
+        println!("Starting a race with seed {}", self.start.seed);
+        for id in &racers {
+            self.send(*id, &ServerMessage::Start(self.start));
+        }
+        for id in &racers {
+            self.broadcast_progress(*id);
+        }
+    }
+
+    fn finish_race_if_done(&mut self) {
+        let still_playing = self
+            .clients
+            .values()
+            .any(|client| client.racing && client.status == PlayerStatus::Playing);
+
+        if still_playing {
+            return;
+        }
+
+        self.clients
+            .values_mut()
+            .for_each(|client| client.racing = false);
+        self.start_race_if_ready();
+    }
+
+    /// Tells the other racers about the progress of `id`.
+    fn broadcast_progress(&mut self, id: u32) {
+        let Some(client) = self.clients.get(&id) else {
+            return;
+        };
+
+        let message = ServerMessage::Opponent(OpponentProgress {
+            player_id: id,
+            name: client.name.clone(),
+            percent: client.percent,
+            status: client.status,
+        });
+
+        let others: Vec<u32> = self
+            .clients
+            .iter()
+            .filter(|(other, client)| **other != id && client.racing)
+            .map(|(other, _)| *other)
+            .collect();
+
+        for other in others {
+            self.send(other, &message);
+        }
+    }
+
+    fn send(&self, id: u32, message: &ServerMessage) {
+        if let Some(client) = self.clients.get(&id) {
+            // the writer only stops once the client is gone
+            let _ = client.outbox.send(message.clone());
+        }
+    }
+}
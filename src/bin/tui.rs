@@ -0,0 +1,352 @@
+//! Terminal front-end, playing the levels of `config.json` with the same rules
+//! as the window.
+//!
+//! ```bash
+//! cargo run --bin tui
+//! ```
+//!
+//! Arrows or hjkl move the cursor, `[` and `]` change the layer, space or enter
+//! reveals, `f` flags, `r` restarts, escape goes back to the menu and `q` quits.
+//! The left and right mouse buttons reveal and flag.
+
+use std::{
+    io::{self, Stdout, Write},
+    time::Duration,
+};
+
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+        MouseButton, MouseEvent, MouseEventKind,
+    },
+    execute, queue,
+    style::{
+        Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor,
+    },
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use minesweeper::{
+    bot::{VisibleBoard, VisibleTile},
+    config::Config,
+    game::{Game, GameState},
+    utils::{current_time_seconds, get_time_diff},
+    vector3::Vector3,
+};
+
+/// Terminal columns used by a tile.
+const TILE_WIDTH: u16 = 2;
+/// Empty tiles drawn between two layers of the board.
+const LAYER_GAP: u16 = 1;
+/// Terminal rows above the board.
+const BOARD_TOP: u16 = 2;
+/// Redraws the clock while no input comes in.
+const TICK: Duration = Duration::from_millis(250);
+
+enum Screen {
+    Menu,
+    Playing,
+}
+
+struct Tui {
+    config: Config,
+    game: Game,
+    screen: Screen,
+    /// selected level in the menu, and the one being played
+    level: usize,
+    cursor: Vector3<i32>,
+}
+
+/// Puts the terminal back the way it was, also when the game panics.
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(
+            io::stdout(),
+            DisableMouseCapture,
+            Show,
+            LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn main() -> io::Result<()> {
+    let config = Config::new();
+    if config.levels.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "config.json has no levels to play",
+        ));
+    }
+
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    let _guard = TerminalGuard;
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture, Hide)?;
+
+    let mut game = Game::headless();
+    game.set_record_stats(true);
+
+    let mut tui = Tui {
+        config,
+        game,
+        screen: Screen::Menu,
+        level: 0,
+        cursor: Vector3::new(0, 0, 0),
+    };
+
+    loop {
+        tui.draw(&mut stdout)?;
+
+        if !event::poll(TICK)? {
+            continue;
+        }
+
+        let quit = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => tui.handle_key(key),
+            Event::Mouse(mouse) => {
+                tui.handle_mouse(mouse);
+                false
+            }
+            _ => false,
+        };
+
+        if quit {
+            return Ok(());
+        }
+    }
+}
+
+impl Tui {
+    /// Returns true when the player quits.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.code == KeyCode::Char('q') {
+            return true;
+        }
+
+        match self.screen {
+            Screen::Menu => match key.code {
+                KeyCode::Up | KeyCode::Char('k') => self.level = self.level.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.level = (self.level + 1).min(self.config.levels.len().saturating_sub(1))
+                }
+                KeyCode::Enter | KeyCode::Char(' ') => self.start(),
+                KeyCode::Esc => return true,
+                _ => {}
+            },
+            Screen::Playing => match key.code {
+                KeyCode::Up | KeyCode::Char('k') => self.move_cursor(Vector3::new(-1, 0, 0)),
+                KeyCode::Down | KeyCode::Char('j') => self.move_cursor(Vector3::new(1, 0, 0)),
+                KeyCode::Left | KeyCode::Char('h') => self.move_cursor(Vector3::new(0, -1, 0)),
+                KeyCode::Right | KeyCode::Char('l') => self.move_cursor(Vector3::new(0, 1, 0)),
+                KeyCode::Char('[') => self.move_cursor(Vector3::new(0, 0, -1)),
+                KeyCode::Char(']') => self.move_cursor(Vector3::new(0, 0, 1)),
+                KeyCode::Enter | KeyCode::Char(' ') => self.game.reveal(self.cursor),
+                KeyCode::Char('f') if self.game.get_state() == GameState::Playing => {
+                    self.game.toggle_flag(self.cursor)
+                }
+                KeyCode::Char('r') => self.start(),
+                KeyCode::Esc => self.screen = Screen::Menu,
+                _ => {}
+            },
+        }
+
+        false
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        let MouseEventKind::Down(button) = mouse.kind else {
+            return;
+        };
+
+        match self.screen {
+            Screen::Menu => {
+                let index = mouse.row.checked_sub(BOARD_TOP).map(usize::from);
+                if let Some(index) = index.filter(|index| *index < self.config.levels.len()) {
+                    self.level = index;
+                    self.start();
+                }
+            }
+            Screen::Playing => {
+                let Some(pos) = self.tile_at(mouse.column, mouse.row) else {
+                    return;
+                };
+
+                self.cursor = pos;
+                match button {
+                    MouseButton::Left => self.game.reveal(pos),
+                    MouseButton::Right if self.game.get_state() == GameState::Playing => {
+                        self.game.toggle_flag(pos)
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn start(&mut self) {
+        self.game.start_level(&self.config.levels[self.level]);
+        self.screen = Screen::Playing;
+        self.cursor = Vector3::new(0, 0, 0);
+    }
+
+    fn move_cursor(&mut self, diff: Vector3<i32>) {
+        if self.game.get_state() == GameState::NotStarted {
+            return;
+        }
+
+        let dimensions = self.game.dimensions;
+        let pos = self.cursor.add(diff);
+
+        self.cursor = Vector3::new(
+            pos.x.clamp(0, dimensions.x - 1),
+            pos.y.clamp(0, dimensions.y - 1),
+            pos.z.clamp(0, dimensions.z - 1),
+        );
+    }
+
+    /// The tile drawn at a terminal cell.
+    fn tile_at(&self, column: u16, row: u16) -> Option<Vector3<i32>> {
+        let dimensions = self.game.dimensions;
+        let layer_width = (dimensions.y as u16 + LAYER_GAP) * TILE_WIDTH;
+
+        let row = row.checked_sub(BOARD_TOP)? as i32;
+        let layer = (column / layer_width) as i32;
+        let col = (column % layer_width / TILE_WIDTH) as i32;
+
+        let pos = Vector3::new(row, col, layer);
+        let board = self.game.visible_board();
+        board.within_bounds(pos).then_some(pos)
+    }
+
+    fn draw(&self, stdout: &mut Stdout) -> io::Result<()> {
+        queue!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
+
+        match self.screen {
+            Screen::Menu => self.draw_menu(stdout)?,
+            Screen::Playing => self.draw_game(stdout)?,
+        }
+
+        stdout.flush()
+    }
+
+    fn draw_menu(&self, stdout: &mut Stdout) -> io::Result<()> {
+        queue!(
+            stdout,
+            Print("Minesweeper - pick a level (enter to start, q to quit)")
+        )?;
+
+        for (i, level) in self.config.levels.iter().enumerate() {
+            queue!(stdout, MoveTo(0, BOARD_TOP + i as u16))?;
+            if i == self.level {
+                queue!(stdout, SetAttribute(Attribute::Reverse))?;
+            }
+            queue!(
+                stdout,
+                Print(format!(" {} ", level.name)),
+                SetAttribute(Attribute::Reset)
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn draw_game(&self, stdout: &mut Stdout) -> io::Result<()> {
+        let board = self.game.visible_board();
+        self.draw_header(stdout, &board)?;
+
+        let dimensions = board.dimensions;
+        for layer in 0..dimensions.z {
+            let left = layer as u16 * (dimensions.y as u16 + LAYER_GAP) * TILE_WIDTH;
+
+            for row in 0..dimensions.x {
+                queue!(stdout, MoveTo(left, BOARD_TOP + row as u16))?;
+
+                for col in 0..dimensions.y {
+                    let pos = Vector3::new(row, col, layer);
+                    if pos == self.cursor {
+                        queue!(stdout, SetAttribute(Attribute::Reverse))?;
+                    }
+
+                    let (text, foreground, background) = tile_style(board.get(pos));
+                    queue!(
+                        stdout,
+                        SetForegroundColor(foreground),
+                        SetBackgroundColor(background),
+                        Print(text),
+                        ResetColor,
+                        SetAttribute(Attribute::Reset)
+                    )?;
+                }
+            }
+        }
+
+        let help_row = BOARD_TOP + dimensions.x as u16 + 1;
+        queue!(
+            stdout,
+            MoveTo(0, help_row),
+            Print("space reveal  f flag  [ ] layer  r restart  esc menu  q quit")
+        )?;
+
+        Ok(())
+    }
+
+    fn draw_header(&self, stdout: &mut Stdout, board: &VisibleBoard) -> io::Result<()> {
+        let flagged = board
+            .tiles
+            .iter()
+            .filter(|tile| **tile == VisibleTile::Flagged)
+            .count() as i32;
+
+        let state = self.game.get_state();
+        let end_time = match state {
+            GameState::GameOver | GameState::GameWon => self.game.end_time,
+            _ => current_time_seconds(),
+        };
+        let (mins, secs) = get_time_diff(self.game.start_time, end_time);
+
+        let status = match state {
+            GameState::GameOver => "Game over! r to restart",
+            GameState::GameWon => "You win! r to play again",
+            _ => "",
+        };
+
+        queue!(
+            stdout,
+            Print(format!(
+                "{}   mines: {}   time: {:02}:{:02}   {}",
+                self.config.levels[self.level].name,
+                board.mines - flagged,
+                mins,
+                secs,
+                status
+            ))
+        )
+    }
+}
+
+/// Text and colours of a tile, two columns wide.
+fn tile_style(tile: VisibleTile) -> (String, Color, Color) {
+    match tile {
+        VisibleTile::Hidden => (" .".to_string(), Color::White, Color::DarkBlue),
+        VisibleTile::Flagged => (" F".to_string(), Color::Red, Color::DarkBlue),
+        VisibleTile::Mine => (" *".to_string(), Color::White, Color::Red),
+        VisibleTile::Number(0) => ("  ".to_string(), Color::Black, Color::Grey),
+        VisibleTile::Number(number) => {
+            let color = match number {
+                1 => Color::Blue,
+                2 => Color::DarkGreen,
+                3 => Color::Red,
+                4 => Color::DarkBlue,
+                5 => Color::DarkRed,
+                6 => Color::DarkCyan,
+                7 => Color::Black,
+                8 => Color::DarkGrey,
+                _ => Color::DarkMagenta,
+            };
+            (format!("{:>2}", number), color, Color::Grey)
+        }
+    }
+}
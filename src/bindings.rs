@@ -0,0 +1,172 @@
+//! Keys of the commands that can be rebound in the settings, kept by name in
+//! `settings.json`. Zooming and panning keep their keys.
+
+use std::collections::HashMap;
+
+use macroquad::input::{is_key_pressed, KeyCode};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+    GameMenu,
+    Restart,
+    NewBoard,
+    Hint,
+    ExportBoard,
+    NextTheme,
+    NextPalette,
+    Mute,
+    SkipAnimations,
+    Diagnostics,
+}
+
+/// Keys that can be bound, named as in [`KeyCode`].
+const KEYS: [KeyCode; 51] = [
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::Space,
+    KeyCode::Tab,
+    KeyCode::Enter,
+    KeyCode::Escape,
+];
+
+impl Command {
+    pub const ALL: [Command; 10] = [
+        Command::GameMenu,
+        Command::Restart,
+        Command::NewBoard,
+        Command::Hint,
+        Command::ExportBoard,
+        Command::NextTheme,
+        Command::NextPalette,
+        Command::Mute,
+        Command::SkipAnimations,
+        Command::Diagnostics,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::GameMenu => "game menu",
+            Command::Restart => "restart the board",
+            Command::NewBoard => "new board",
+            Command::Hint => "hint",
+            Command::ExportBoard => "export the board",
+            Command::NextTheme => "next theme",
+            Command::NextPalette => "next palette",
+            Command::Mute => "mute",
+            Command::SkipAnimations => "skip animations",
+            Command::Diagnostics => "diagnostics",
+        }
+    }
+
+    fn default_key(&self) -> KeyCode {
+        match self {
+            Command::GameMenu => KeyCode::Escape,
+            Command::Restart => KeyCode::F3,
+            Command::NewBoard => KeyCode::F2,
+            Command::Hint => KeyCode::H,
+            Command::ExportBoard => KeyCode::E,
+            Command::NextTheme => KeyCode::T,
+            Command::NextPalette => KeyCode::C,
+            Command::Mute => KeyCode::M,
+            Command::SkipAnimations => KeyCode::Space,
+            Command::Diagnostics => KeyCode::F12,
+        }
+    }
+}
+
+pub fn key_name(key: KeyCode) -> String {
+    format!("{:?}", key)
+}
+
+/// The key of a name written by [`key_name`], if it can be bound.
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    KEYS.into_iter().find(|key| key_name(*key) == name)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bindings {
+    /// names of the keys of the rebound commands, the others keep their default
+    pub keys: HashMap<Command, String>,
+    /// flag with the left button and reveal with the right one
+    pub swap_mouse_buttons: bool,
+}
+
+impl Bindings {
+    pub fn key(&self, command: Command) -> KeyCode {
+        self.keys
+            .get(&command)
+            .and_then(|name| parse_key(name))
+            .unwrap_or(command.default_key())
+    }
+
+    /// Binds the key to the command, false when the key can't be bound. A
+    /// command that had the key takes the previous key of this one.
+    pub fn bind(&mut self, command: Command, key: KeyCode) -> bool {
+        if parse_key(&key_name(key)).is_none() {
+            return false;
+        }
+
+        let previous = self.key(command);
+        if let Some(other) = Command::ALL
+            .into_iter()
+            .find(|other| *other != command && self.key(*other) == key)
+        {
+            self.keys.insert(other, key_name(previous));
+        }
+        self.keys.insert(command, key_name(key));
+
+        true
+    }
+
+    pub fn is_pressed(&self, command: Command) -> bool {
+        is_key_pressed(self.key(command))
+    }
+}
@@ -0,0 +1,196 @@
+//! Large boards are drawn into a texture kept between frames, drawing again
+//! only the tiles that look different, so showing the board costs a single
+//! quad however many tiles it has.
+
+use std::collections::BTreeSet;
+
+use macroquad::{
+    camera::{set_camera, set_default_camera, Camera2D},
+    color::{Color, WHITE},
+    math::{vec2, Rect},
+    shapes::draw_rectangle,
+    texture::{draw_texture_ex, render_target, DrawTextureParams, FilterMode, RenderTarget},
+};
+
+use crate::{
+    theme::Theme,
+    tile::{Tile, TileState},
+    vector2::Vector2,
+};
+
+/// Boards with fewer tiles are as quick to draw tile by tile.
+const MIN_TILES: usize = 10_000;
+/// Largest side of the texture, which any GPU can take.
+const MAX_TEXTURE_SIZE: f32 = 4096.0;
+/// Pixels of a tile in the texture.
+const MAX_TILE_SIZE: f32 = 32.0;
+const MIN_TILE_SIZE: f32 = 4.0;
+
+/// What a tile is drawn from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileLook {
+    tile: Tile,
+    outline: Option<Color>,
+    game_over: bool,
+}
+
+impl TileLook {
+    pub fn new(tile: Tile, outline: Option<Color>, game_over: bool) -> Self {
+        // the end of the game only changes how flags look
+        let game_over = game_over && tile.state == TileState::Flagged;
+
+        TileLook {
+            tile,
+            outline,
+            game_over,
+        }
+    }
+
+    pub fn draw(&self, pos: Vector2<f32>, size: f32, theme: &Theme) {
+        self.tile
+            .draw(pos, size, theme, self.outline, self.game_over);
+    }
+}
+
+/// Tiles that may look different since the cache was last updated, marked
+/// by the moves, hints and animations so a frame only looks at those.
+#[derive(Debug, Default)]
+pub struct DirtyTiles {
+    /// every tile, for a new board or the end of a game
+    all: bool,
+    tiles: BTreeSet<usize>,
+}
+
+impl DirtyTiles {
+    pub fn mark(&mut self, index: usize) {
+        if !self.all {
+            self.tiles.insert(index);
+        }
+    }
+
+    pub fn mark_all(&mut self) {
+        self.all = true;
+        self.tiles.clear();
+    }
+
+    /// The marked tiles, none when all of them are, leaving nothing marked.
+    pub fn take(&mut self) -> Option<BTreeSet<usize>> {
+        let all = std::mem::take(&mut self.all);
+        let tiles = std::mem::take(&mut self.tiles);
+
+        (!all).then_some(tiles)
+    }
+}
+
+#[derive(Debug)]
+pub struct BoardCache {
+    target: RenderTarget,
+    /// columns and rows of the board, with the gaps between layers
+    cells: Vector2<f32>,
+    /// pixels of a tile in the texture
+    tile_size: f32,
+    background: Color,
+    /// how each tile was last drawn, by index
+    drawn: Vec<Option<TileLook>>,
+}
+
+impl BoardCache {
+    /// Pixels of a tile in the texture of a board, none when the board is
+    /// small enough to draw directly or too large for a texture.
+    pub fn tile_size(cells: Vector2<f32>, tiles: usize) -> Option<f32> {
+        let size = (MAX_TEXTURE_SIZE / cells.max_component())
+            .floor()
+            .min(MAX_TILE_SIZE);
+
+        (tiles >= MIN_TILES && size >= MIN_TILE_SIZE).then_some(size)
+    }
+
+    pub fn new(cells: Vector2<f32>, tile_size: f32, tiles: usize, background: Color) -> Self {
+        let size = cells.scale(tile_size);
+        let target = render_target(size.x as u32, size.y as u32);
+        target.texture.set_filter(FilterMode::Linear);
+
+        let cache = BoardCache {
+            target,
+            cells,
+            tile_size,
+            background,
+            drawn: vec![None; tiles],
+        };
+
+        // clearing would also drop what was drawn on the screen this frame
+        set_camera(&cache.camera());
+        draw_rectangle(0.0, 0.0, size.x, size.y, background);
+        set_default_camera();
+
+        cache
+    }
+
+    pub fn fits(&self, cells: Vector2<f32>, tile_size: f32) -> bool {
+        self.cells == cells && self.tile_size == tile_size
+    }
+
+    /// Draws into the texture the tiles that changed since they were last
+    /// drawn, from their index, their cell on the board and their look.
+    /// Returns how many were drawn.
+    pub fn update(
+        &mut self,
+        looks: impl Iterator<Item = (usize, Vector2<f32>, TileLook)>,
+        theme: &Theme,
+    ) -> usize {
+        let mut drawn = 0;
+        for (index, cell, look) in looks {
+            if self.drawn[index].as_ref() == Some(&look) {
+                continue;
+            }
+            if drawn == 0 {
+                set_camera(&self.camera());
+            }
+            drawn += 1;
+
+            // covers the last look, which a texture with see-through parts
+            // would show through
+            let pos = cell.scale(self.tile_size);
+            draw_rectangle(
+                pos.x,
+                pos.y,
+                self.tile_size,
+                self.tile_size,
+                self.background,
+            );
+            look.draw(pos, self.tile_size - 1.0, theme);
+            self.drawn[index] = Some(look);
+        }
+
+        if drawn > 0 {
+            set_default_camera();
+        }
+
+        drawn
+    }
+
+    /// Draws the whole board at `pos` scaled to `size`.
+    pub fn draw(&self, pos: Vector2<f32>, size: Vector2<f32>) {
+        draw_texture_ex(
+            &self.target.texture,
+            pos.x,
+            pos.y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(size.x, size.y)),
+                // render targets are upside down
+                flip_y: true,
+                ..Default::default()
+            },
+        );
+    }
+
+    fn camera(&self) -> Camera2D {
+        let size = self.cells.scale(self.tile_size);
+
+        Camera2D {
+            render_target: Some(self.target.clone()),
+            ..Camera2D::from_display_rect(Rect::new(0.0, 0.0, size.x, size.y))
+        }
+    }
+}
@@ -0,0 +1,255 @@
+//! Plain-text layout of a board, one character per tile:
+//!
+//! - `.` hidden tile without a mine
+//! - `*` hidden tile with a mine
+//! - `F` flagged tile with a mine
+//! - `f` flagged tile without a mine (a wrong flag)
+//! - `X` revealed mine
+//! - `0`-`9` revealed tile, `+` for layered boards with more than 9 mines around
+//!
+//! Rows are separated by new lines and the layers of a 3D board by an empty
+//! line. Lines starting with `;` are comments. The numbers of revealed tiles
+//! are only written for readability, they are recomputed from the mines when
+//! the board is loaded.
+
+use std::{error::Error, fmt, fs, io, path::Path};
+
+use crate::{
+    tile::{Tile, TileState},
+    vector3::Vector3,
+};
+
+#[derive(Debug)]
+pub enum BoardTextError {
+    Io(io::Error),
+    Empty,
+    UnknownTile { line: usize, tile: char },
+    RaggedRow { line: usize },
+    RaggedLayer { layer: usize },
+}
+
+impl fmt::Display for BoardTextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardTextError::Io(err) => write!(f, "could not read the board: {}", err),
+            BoardTextError::Empty => write!(f, "the board has no tiles"),
+            BoardTextError::UnknownTile { line, tile } => {
+                write!(f, "line {}: unknown tile '{}'", line, tile)
+            }
+            BoardTextError::RaggedRow { line } => {
+                write!(f, "line {}: row length differs from the first row", line)
+            }
+            BoardTextError::RaggedLayer { layer } => {
+                write!(f, "layer {}: height differs from the first layer", layer)
+            }
+        }
+    }
+}
+
+impl Error for BoardTextError {}
+
+impl From<io::Error> for BoardTextError {
+    fn from(err: io::Error) -> Self {
+        BoardTextError::Io(err)
+    }
+}
+
+pub fn read_board(path: impl AsRef<Path>) -> Result<(Vector3<i32>, Vec<Tile>), BoardTextError> {
+    from_text(&fs::read_to_string(path)?)
+}
+
+pub fn write_board(
+    path: impl AsRef<Path>,
+    dimensions: Vector3<i32>,
+    tiles: &[Tile],
+) -> io::Result<()> {
+    if let Some(dir) = path.as_ref().parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    fs::write(path, to_text(dimensions, tiles))
+}
+
+pub fn to_text(dimensions: Vector3<i32>, tiles: &[Tile]) -> String {
+    let cols = dimensions.y as usize;
+    let layer_size = dimensions.x as usize * cols;
+
+    tiles
+        .chunks(layer_size.max(1))
+        .map(|layer| {
+            layer
+                .chunks(cols.max(1))
+                .map(|row| row.iter().map(tile_to_char).collect::<String>() + "\n")
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn from_text(text: &str) -> Result<(Vector3<i32>, Vec<Tile>), BoardTextError> {
+    let mut layers: Vec<Vec<Vec<Tile>>> = vec![Vec::new()];
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+
+        if line.starts_with(';') {
+            continue;
+        }
+
+        if line.is_empty() {
+            if layers.last().is_some_and(|layer| !layer.is_empty()) {
+                layers.push(Vec::new());
+            }
+            continue;
+        }
+
+        let row = line
+            .chars()
+            .map(|tile| {
+                char_to_tile(tile).ok_or(BoardTextError::UnknownTile {
+                    line: line_number,
+                    tile,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let first_row_len = layers.first().and_then(|layer| layer.first()).map(Vec::len);
+        if first_row_len.is_some_and(|len| len != row.len()) {
+            return Err(BoardTextError::RaggedRow { line: line_number });
+        }
+
+        if let Some(layer) = layers.last_mut() {
+            layer.push(row);
+        }
+    }
+
+    if layers.last().is_some_and(|layer| layer.is_empty()) {
+        layers.pop();
+    }
+
+    let rows = layers.first().map_or(0, Vec::len);
+    if rows == 0 {
+        return Err(BoardTextError::Empty);
+    }
+
+    if let Some(index) = layers.iter().position(|layer| layer.len() != rows) {
+        return Err(BoardTextError::RaggedLayer { layer: index + 1 });
+    }
+
+    let cols = layers[0][0].len();
+    let dimensions = Vector3::new(rows as i32, cols as i32, layers.len() as i32);
+    let tiles = layers.into_iter().flatten().flatten().collect();
+
+    Ok((dimensions, tiles))
+}
+
+fn tile_to_char(tile: &Tile) -> char {
+    match (tile.state, tile.has_mine) {
+        (TileState::Hidden, false) => '.',
+        (TileState::Hidden, true) => '*',
+        (TileState::Flagged, true) => 'F',
+        (TileState::Flagged, false) => 'f',
+        (TileState::Revealed, true) => 'X',
+        (TileState::Revealed, false) => match tile.num_mines_around {
+            count @ 0..=9 => char::from_digit(count as u32, 10).unwrap_or('+'),
+            _ => '+',
+        },
+    }
+}
+
+fn char_to_tile(c: char) -> Option<Tile> {
+    let (state, has_mine) = match c {
+        '.' => (TileState::Hidden, false),
+        '*' => (TileState::Hidden, true),
+        'F' => (TileState::Flagged, true),
+        'f' => (TileState::Flagged, false),
+        'X' => (TileState::Revealed, true),
+        '0'..='9' | '+' => (TileState::Revealed, false),
+        _ => return None,
+    };
+
+    Some(Tile {
+        has_mine,
+        state,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn count(tiles: &[Tile], state: TileState, has_mine: bool) -> usize {
+        tiles
+            .iter()
+            .filter(|tile| tile.state == state && tile.has_mine == has_mine)
+            .count()
+    }
+
+    #[test]
+    fn fixtures_parse() {
+        let mut fixtures = 0;
+        for entry in fs::read_dir("boards").unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|extension| extension == "txt") {
+                let (dimensions, tiles) = read_board(&path).unwrap();
+                let Vector3 { x, y, z } = dimensions;
+                assert_eq!(tiles.len(), (x * y * z) as usize, "{:?}", path);
+                fixtures += 1;
+            }
+        }
+        assert!(fixtures > 0);
+    }
+
+    #[test]
+    fn one_two_one_fixture() {
+        let (dimensions, tiles) = read_board("boards/one-two-one.txt").unwrap();
+        assert_eq!(dimensions, Vector3::new(9, 9, 1));
+        assert_eq!(count(&tiles, TileState::Hidden, true), 6);
+        assert_eq!(count(&tiles, TileState::Hidden, false), 10);
+        assert_eq!(count(&tiles, TileState::Revealed, false), 65);
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = "0.*\nFfX\n\n...\n*..\n";
+        let (dimensions, tiles) = from_text(text).unwrap();
+        assert_eq!(dimensions, Vector3::new(2, 3, 2));
+        assert_eq!(to_text(dimensions, &tiles), text);
+    }
+
+    #[test]
+    fn comments_and_padding_are_skipped() {
+        let (dimensions, tiles) = from_text("; a comment\n  .*  \n\n\n; between\n**\n\n").unwrap();
+        assert_eq!(dimensions, Vector3::new(1, 2, 2));
+        assert_eq!(count(&tiles, TileState::Hidden, true), 3);
+    }
+
+    #[test]
+    fn malformed_text_fails() {
+        assert!(matches!(
+            from_text("..\n.?\n"),
+            Err(BoardTextError::UnknownTile { line: 2, tile: '?' })
+        ));
+        assert!(matches!(
+            from_text("...\n..\n"),
+            Err(BoardTextError::RaggedRow { line: 2 })
+        ));
+        assert!(matches!(
+            from_text("..\n..\n\n..\n"),
+            Err(BoardTextError::RaggedLayer { layer: 2 })
+        ));
+        assert!(matches!(from_text(""), Err(BoardTextError::Empty)));
+        assert!(matches!(
+            from_text("; only a comment\n\n"),
+            Err(BoardTextError::Empty)
+        ));
+        assert!(matches!(
+            read_board("boards/missing.txt"),
+            Err(BoardTextError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,292 @@
+//! Bots play through what a player can see of the board, which makes them
+//! usable to evaluate solvers and the difficulty of generated boards.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{Game, GameState, NEIGHBORS},
+    vector3::Vector3,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VisibleTile {
+    Hidden,
+    Flagged,
+    /// a revealed safe tile and the number of mines around it
+    Number(i32),
+    /// a revealed mine, only seen once the game is lost
+    Mine,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VisibleBoard {
+    /// rows, cols and layers of the board
+    pub dimensions: Vector3<i32>,
+    pub mines: i32,
+    pub tiles: Vec<VisibleTile>,
+}
+
+impl VisibleBoard {
+    pub fn get(&self, pos: Vector3<i32>) -> VisibleTile {
+        self.tiles[self.index(pos)]
+    }
+
+    pub fn within_bounds(&self, pos: Vector3<i32>) -> bool {
+        pos.x >= 0
+            && pos.y >= 0
+            && pos.z >= 0
+            && pos.x < self.dimensions.x
+            && pos.y < self.dimensions.y
+            && pos.z < self.dimensions.z
+    }
+
+    pub fn index(&self, pos: Vector3<i32>) -> usize {
+        let layer_start = pos.z as usize * self.dimensions.x as usize;
+        (layer_start + pos.x as usize) * self.dimensions.y as usize + pos.y as usize
+    }
+
+    pub fn position(&self, index: usize) -> Vector3<i32> {
+        let index = index as i32;
+        let cols = self.dimensions.y;
+        let rows = self.dimensions.x;
+
+        Vector3::new(index / cols % rows, index % cols, index / (cols * rows))
+    }
+
+    pub fn neighbors(&self, pos: Vector3<i32>) -> impl Iterator<Item = Vector3<i32>> + '_ {
+        NEIGHBORS
+            .iter()
+            .map(move |diff| pos.add(*diff))
+            .filter(|pos| self.within_bounds(*pos))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BotAction {
+    /// reveal a tile known to be safe
+    Reveal(Vector3<i32>),
+    /// reveal a tile that may hide a mine
+    Guess(Vector3<i32>),
+    Flag(Vector3<i32>),
+}
+
+pub trait Bot {
+    fn name(&self) -> &str;
+
+    fn next_action(&mut self, board: &VisibleBoard) -> BotAction;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BotGame {
+    pub won: bool,
+    pub moves: i32,
+    pub guesses: i32,
+}
+
+/// Lets `bot` play the started `game` until it ends.
+pub fn play(bot: &mut dyn Bot, game: &mut Game) -> BotGame {
+    let mut result = BotGame {
+        won: false,
+        moves: 0,
+        guesses: 0,
+    };
+
+    // a bot repeating a move that changes nothing would never finish
+    let max_moves = game.visible_board().tiles.len() as i32 * 2;
+
+    while game.get_state() == GameState::Playing && result.moves < max_moves {
+        match bot.next_action(&game.visible_board()) {
+            BotAction::Reveal(pos) => game.reveal(pos),
+            BotAction::Guess(pos) => {
+                result.guesses += 1;
+                game.reveal(pos);
+            }
+            BotAction::Flag(pos) => game.toggle_flag(pos),
+        }
+        result.moves += 1;
+    }
+
+    result.won = game.get_state() == GameState::GameWon;
+    result
+}
+
+/// Deductions of the solver, from the easiest to the hardest to spot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Technique {
+    /// one number has as many hidden tiles as mines left, or none
+    Single,
+    /// the hidden tiles of one number are all around another one
+    Subset,
+    /// the mines left on the board settle the last tiles
+    MineCount,
+}
+
+/// What a revealed number says about the hidden tiles around it.
+struct Constraint {
+    pos: Vector3<i32>,
+    hidden: BTreeSet<usize>,
+    mines: i32,
+}
+
+/// A certain move and the numbers it follows from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deduction {
+    pub action: BotAction,
+    pub technique: Technique,
+    /// the numbers the move follows from, none for the mine count
+    pub reasons: Vec<Vector3<i32>>,
+}
+
+/// Deduces safe tiles and mines from single numbers and from pairs of
+/// numbers whose hidden tiles overlap, and guesses the least risky tile when
+/// nothing is certain.
+#[derive(Debug, Default)]
+pub struct Solver;
+
+impl Solver {
+    pub fn new() -> Self {
+        Solver
+    }
+
+    fn constraints(board: &VisibleBoard) -> Vec<Constraint> {
+        (0..board.tiles.len())
+            .filter_map(|index| {
+                let VisibleTile::Number(number) = board.tiles[index] else {
+                    return None;
+                };
+
+                let pos = board.position(index);
+                let mut hidden = BTreeSet::new();
+                let mut mines = number;
+                for other in board.neighbors(pos) {
+                    match board.get(other) {
+                        VisibleTile::Hidden => {
+                            hidden.insert(board.index(other));
+                        }
+                        VisibleTile::Flagged | VisibleTile::Mine => mines -= 1,
+                        VisibleTile::Number(_) => {}
+                    }
+                }
+
+                (!hidden.is_empty()).then_some(Constraint { pos, hidden, mines })
+            })
+            .collect()
+    }
+
+    /// The easiest certain move on the board, if there is one.
+    pub fn deduce(board: &VisibleBoard) -> Option<Deduction> {
+        Solver::deduce_from(board, &Solver::constraints(board))
+    }
+
+    fn deduce_from(board: &VisibleBoard, constraints: &[Constraint]) -> Option<Deduction> {
+        let certain = |tiles: &BTreeSet<usize>, mines: i32| {
+            let first = board.position(*tiles.iter().next()?);
+            match mines {
+                0 => Some(BotAction::Reveal(first)),
+                _ if mines == tiles.len() as i32 => Some(BotAction::Flag(first)),
+                _ => None,
+            }
+        };
+
+        if let Some(deduction) = constraints.iter().find_map(|constraint| {
+            Some(Deduction {
+                action: certain(&constraint.hidden, constraint.mines)?,
+                technique: Technique::Single,
+                reasons: vec![constraint.pos],
+            })
+        }) {
+            return Some(deduction);
+        }
+
+        // when the tiles of one number are all around another one, the
+        // difference between the two holds the difference of their mines
+        for (i, smaller) in constraints.iter().enumerate() {
+            for (j, larger) in constraints.iter().enumerate() {
+                if i == j
+                    || larger.hidden.len() <= smaller.hidden.len()
+                    || !smaller.hidden.is_subset(&larger.hidden)
+                {
+                    continue;
+                }
+
+                let rest = &larger.hidden - &smaller.hidden;
+                if let Some(action) = certain(&rest, larger.mines - smaller.mines) {
+                    return Some(Deduction {
+                        action,
+                        technique: Technique::Subset,
+                        reasons: vec![smaller.pos, larger.pos],
+                    });
+                }
+            }
+        }
+
+        let hidden: Vec<usize> = (0..board.tiles.len())
+            .filter(|index| board.tiles[*index] == VisibleTile::Hidden)
+            .collect();
+        certain(&hidden.into_iter().collect(), Solver::mines_left(board)).map(|action| Deduction {
+            action,
+            technique: Technique::MineCount,
+            reasons: Vec::new(),
+        })
+    }
+
+    fn mines_left(board: &VisibleBoard) -> i32 {
+        let known_mines = board
+            .tiles
+            .iter()
+            .filter(|tile| matches!(tile, VisibleTile::Flagged | VisibleTile::Mine))
+            .count() as i32;
+
+        board.mines - known_mines
+    }
+
+    /// The next move and the technique that made it certain, none for guesses.
+    pub fn solve(board: &VisibleBoard) -> (BotAction, Option<Technique>) {
+        let constraints = Solver::constraints(board);
+        if let Some(deduction) = Solver::deduce_from(board, &constraints) {
+            return (deduction.action, Some(deduction.technique));
+        }
+
+        let hidden: Vec<usize> = (0..board.tiles.len())
+            .filter(|index| board.tiles[*index] == VisibleTile::Hidden)
+            .collect();
+
+        if constraints.is_empty() && hidden.len() == board.tiles.len() {
+            let Vector3 { x, y, z } = board.dimensions;
+            return (BotAction::Guess(Vector3::new(x / 2, y / 2, z / 2)), None);
+        }
+
+        // the risk of a tile is the highest share of mines any number around
+        // it leaves to its hidden tiles, away from numbers it is the density
+        let density = Solver::mines_left(board) as f32 / hidden.len().max(1) as f32;
+        let risk = |index: usize| {
+            constraints
+                .iter()
+                .filter(|constraint| constraint.hidden.contains(&index))
+                .map(|constraint| constraint.mines as f32 / constraint.hidden.len() as f32)
+                .reduce(f32::max)
+                .unwrap_or(density)
+        };
+
+        let safest = hidden
+            .iter()
+            .copied()
+            .min_by(|a, b| risk(*a).total_cmp(&risk(*b)))
+            .unwrap_or(0);
+
+        (BotAction::Guess(board.position(safest)), None)
+    }
+}
+
+impl Bot for Solver {
+    fn name(&self) -> &str {
+        "solver"
+    }
+
+    fn next_action(&mut self, board: &VisibleBoard) -> BotAction {
+        Solver::solve(board).0
+    }
+}
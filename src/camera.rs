@@ -0,0 +1,130 @@
+//! Zoom and pan over the board, for boards too large to play at the size that
+//! fits the window. Positions are in pixels from the top left of the body.
+
+use macroquad::{
+    input::{is_key_down, is_key_pressed, is_mouse_button_down, mouse_wheel, KeyCode, MouseButton},
+    time::get_frame_time,
+};
+
+use crate::vector2::Vector2;
+
+const MIN_ZOOM: f32 = 1.0;
+const MAX_ZOOM: f32 = 16.0;
+/// zoom of one notch of the mouse wheel or one key press
+const ZOOM_STEP: f32 = 1.2;
+/// pixels per second when panning with the keys
+const PAN_SPEED: f32 = 600.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub zoom: f32,
+    /// where the top left of the board is drawn
+    pub offset: Vector2<f32>,
+    /// cursor position on the last frame of a drag
+    drag_from: Option<Vector2<f32>>,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera::new()
+    }
+}
+
+impl Camera {
+    pub fn new() -> Self {
+        Camera {
+            zoom: MIN_ZOOM,
+            offset: Vector2::new(0.0, 0.0),
+            drag_from: None,
+        }
+    }
+
+    pub fn is_zoomed(&self) -> bool {
+        self.zoom > MIN_ZOOM
+    }
+
+    /// Position on the board at zoom 1 to position on the screen.
+    pub fn to_screen(&self, pos: Vector2<f32>) -> Vector2<f32> {
+        pos.scale(self.zoom).add(self.offset)
+    }
+
+    /// Position on the screen to position on the board at zoom 1.
+    pub fn to_board(&self, pos: Vector2<f32>) -> Vector2<f32> {
+        pos.sub(self.offset).scale(1.0 / self.zoom)
+    }
+
+    /// Zooms keeping the board under `anchor` in place.
+    pub fn zoom_at(&mut self, factor: f32, anchor: Vector2<f32>) {
+        let board_anchor = self.to_board(anchor);
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.offset = anchor.sub(board_anchor.scale(self.zoom));
+    }
+
+    pub fn pan(&mut self, delta: Vector2<f32>) {
+        self.offset = self.offset.add(delta);
+    }
+
+    /// Moves the view so its centre shows `pos` on the board at zoom 1.
+    pub fn center_on(&mut self, pos: Vector2<f32>, view: Vector2<f32>) {
+        self.offset = view.scale(0.5).sub(pos.scale(self.zoom));
+    }
+
+    /// Keeps the board in view: a board larger than the view can't be panned
+    /// past its edges, a smaller one is centred.
+    pub fn clamp(&mut self, board: Vector2<f32>, view: Vector2<f32>) {
+        let size = board.scale(self.zoom);
+        let clamp_axis = |offset: f32, size: f32, view: f32| match size > view {
+            true => offset.clamp(view - size, 0.0),
+            false => (view - size) / 2.0,
+        };
+
+        self.offset = Vector2::new(
+            clamp_axis(self.offset.x, size.x, view.x),
+            clamp_axis(self.offset.y, size.y, view.y),
+        );
+    }
+
+    /// Zooms with the mouse wheel around the cursor or with `+` and `-`,
+    /// pans by dragging with the middle button or with the arrow keys, and
+    /// `0` zooms back out to the whole board.
+    pub fn handle_input(&mut self, cursor: Vector2<f32>, board: Vector2<f32>, view: Vector2<f32>) {
+        let (_, wheel) = mouse_wheel();
+        if wheel != 0.0 {
+            self.zoom_at(ZOOM_STEP.powf(wheel.signum()), cursor);
+        }
+
+        let center = view.scale(0.5);
+        if is_key_pressed(KeyCode::Equal) || is_key_pressed(KeyCode::KpAdd) {
+            self.zoom_at(ZOOM_STEP, center);
+        }
+        if is_key_pressed(KeyCode::Minus) || is_key_pressed(KeyCode::KpSubtract) {
+            self.zoom_at(1.0 / ZOOM_STEP, center);
+        }
+        if is_key_pressed(KeyCode::Key0) {
+            *self = Camera::new();
+        }
+
+        self.drag_from = match is_mouse_button_down(MouseButton::Middle) {
+            true => {
+                if let Some(from) = self.drag_from {
+                    self.pan(cursor.sub(from));
+                }
+                Some(cursor)
+            }
+            false => None,
+        };
+
+        let step = PAN_SPEED * get_frame_time();
+        let keys = [
+            (KeyCode::Left, Vector2::new(step, 0.0)),
+            (KeyCode::Right, Vector2::new(-step, 0.0)),
+            (KeyCode::Up, Vector2::new(0.0, step)),
+            (KeyCode::Down, Vector2::new(0.0, -step)),
+        ];
+        keys.iter()
+            .filter(|(key, _)| is_key_down(*key))
+            .for_each(|(_, delta)| self.pan(*delta));
+
+        self.clamp(board, view);
+    }
+}
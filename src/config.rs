@@ -2,20 +2,79 @@ use std::{fs::File, io::BufReader};
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::difficulty::DifficultyLevel;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameLevelConfig {
     pub name: String,
     pub rows: i32,
     pub cols: i32,
+    #[serde(default = "default_layers")]
+    pub layers: i32,
     pub mines: i32,
+    /// Path to a text board to play instead of a random one
+    #[serde(default)]
+    pub board: Option<String>,
+    /// Generated boards are picked to match this difficulty
+    #[serde(default)]
+    pub difficulty: Option<DifficultyLevel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PuzzlePackConfig {
+    pub name: String,
+    /// Directory holding the puzzle boards
+    pub dir: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     pub levels: Vec<GameLevelConfig>,
+    /// Level of the daily challenge
+    #[serde(default = "default_daily")]
+    pub daily: GameLevelConfig,
+    /// Level played by players taking turns
+    #[serde(default = "default_hot_seat")]
+    pub hot_seat: GameLevelConfig,
+    /// Level handed out by the server of online races
+    #[serde(default = "default_online")]
+    pub online: GameLevelConfig,
+    #[serde(default)]
+    pub puzzle_packs: Vec<PuzzlePackConfig>,
+}
+
+fn default_layers() -> i32 {
+    1
+}
+
+fn default_daily() -> GameLevelConfig {
+    GameLevelConfig {
+        name: "Daily".to_string(),
+        rows: 16,
+        cols: 16,
+        layers: 1,
+        mines: 40,
+        board: None,
+        difficulty: None,
+    }
+}
+
+fn default_hot_seat() -> GameLevelConfig {
+    GameLevelConfig {
+        name: "Hot-seat".to_string(),
+        ..default_daily()
+    }
+}
+
+fn default_online() -> GameLevelConfig {
+    GameLevelConfig {
+        name: "Online".to_string(),
+        ..default_daily()
+    }
 }
 
 impl Config {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         let file = File::open("./config.json").expect("Could not open the config file");
         let reader = BufReader::new(file);
@@ -0,0 +1,206 @@
+use std::{collections::BTreeMap, fs};
+
+use chrono::{Datelike, Months, NaiveDate};
+use macroquad::{
+    color::{Color, LIGHTGRAY},
+    shapes::draw_rectangle,
+    text::draw_text,
+    window::{screen_height, screen_width},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    grid::ui_scale,
+    messages::font_size,
+    theme::ThemeColors,
+    utils::{get_time_diff, load_json},
+};
+
+const RESULTS_FILE: &str = "./daily.json";
+
+const CELL_SIZE: f32 = 60.0;
+const CELL_PADDING: f32 = 4.0;
+const WEEKDAYS: &[&str] = &["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// The board of a day is the same for everyone, its seed is the date itself.
+pub fn daily_seed(date: NaiveDate) -> u64 {
+    date.year() as u64 * 10_000 + date.month() as u64 * 100 + date.day() as u64
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DailyChallenge {
+    pub date: NaiveDate,
+    /// only the first attempt of the day is ranked, the rest are practice
+    pub ranked: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DailyOutcome {
+    /// the ranked attempt was started but never finished
+    Started,
+    Won,
+    Lost,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DailyResult {
+    pub outcome: DailyOutcome,
+    pub seconds: i64,
+}
+
+/// Results of the ranked daily attempts, keyed by date.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DailyResults {
+    pub results: BTreeMap<NaiveDate, DailyResult>,
+}
+
+impl DailyResults {
+    pub fn load() -> Self {
+        load_json(RESULTS_FILE)
+    }
+
+    /// Starts the daily challenge of `date`, which is ranked when it is the
+    /// first attempt of that day.
+    pub fn start(date: NaiveDate) -> DailyChallenge {
+        let mut results = DailyResults::load();
+        let ranked = !results.results.contains_key(&date);
+
+        if ranked {
+            results.record(
+                date,
+                DailyResult {
+                    outcome: DailyOutcome::Started,
+                    seconds: 0,
+                },
+            );
+        }
+
+        DailyChallenge { date, ranked }
+    }
+
+    pub fn finish(challenge: &DailyChallenge, won: bool, seconds: i64) {
+        if !challenge.ranked {
+            return;
+        }
+
+        let outcome = match won {
+            true => DailyOutcome::Won,
+            false => DailyOutcome::Lost,
+        };
+
+        DailyResults::load().record(challenge.date, DailyResult { outcome, seconds });
+    }
+
+    fn record(&mut self, date: NaiveDate, result: DailyResult) {
+        self.results.insert(date, result);
+
+        let saved = serde_json::to_string_pretty(self)
+            .map_err(|err| err.to_string())
+            .and_then(|json| fs::write(RESULTS_FILE, json).map_err(|err| err.to_string()));
+
+        if let Err(err) = saved {
+            eprintln!("Could not save the daily results: {}", err);
+        }
+    }
+}
+
+/// Month view of the past daily results.
+#[derive(Debug)]
+pub struct DailyCalendar {
+    results: DailyResults,
+    month: NaiveDate,
+}
+
+impl DailyCalendar {
+    pub fn new(today: NaiveDate) -> Self {
+        DailyCalendar {
+            results: DailyResults::load(),
+            month: today.with_day(1).unwrap_or(today),
+        }
+    }
+
+    pub fn previous_month(&mut self) {
+        self.month = self.month - Months::new(1);
+    }
+
+    pub fn next_month(&mut self) {
+        self.month = self.month + Months::new(1);
+    }
+
+    pub fn draw(&self, colors: &ThemeColors) {
+        let cell_size = CELL_SIZE * ui_scale();
+        let cell_padding = CELL_PADDING * ui_scale();
+        let font_size = font_size();
+        let width = cell_size * WEEKDAYS.len() as f32;
+        let left = (screen_width() - width) / 2.0;
+        let top = (screen_height() - cell_size * 7.0) / 2.0;
+
+        draw_text(
+            format!(
+                "{}  (left/right to change month)",
+                self.month.format("%B %Y")
+            ),
+            left,
+            top,
+            font_size * 1.2,
+            colors.text,
+        );
+
+        WEEKDAYS.iter().enumerate().for_each(|(i, day)| {
+            let x = left + i as f32 * cell_size;
+            draw_text(day, x, top + font_size * 2.0, font_size, colors.text);
+        });
+
+        let offset = self.month.weekday().num_days_from_monday();
+        for (i, date) in self.days().enumerate() {
+            let cell = i as u32 + offset;
+            let x = left + (cell % 7) as f32 * cell_size;
+            let y = top + font_size * 2.5 + (cell / 7) as f32 * cell_size;
+
+            let result = self.results.results.get(&date);
+            draw_rectangle(
+                x,
+                y,
+                cell_size - cell_padding,
+                cell_size - cell_padding,
+                DailyCalendar::color(result, colors),
+            );
+            draw_text(
+                date.day().to_string(),
+                x + cell_padding,
+                y + font_size,
+                font_size,
+                colors.text,
+            );
+
+            if let Some(DailyResult {
+                outcome: DailyOutcome::Won,
+                seconds,
+            }) = result
+            {
+                let (mins, secs) = get_time_diff(0, *seconds);
+                draw_text(
+                    format!("{:02}:{:02}", mins, secs),
+                    x + cell_padding,
+                    y + cell_size - font_size,
+                    font_size * 0.8,
+                    colors.text,
+                );
+            }
+        }
+    }
+
+    fn days(&self) -> impl Iterator<Item = NaiveDate> + '_ {
+        self.month
+            .iter_days()
+            .take_while(|date| date.month() == self.month.month())
+    }
+
+    fn color(result: Option<&DailyResult>, colors: &ThemeColors) -> Color {
+        match result.map(|result| result.outcome) {
+            Some(DailyOutcome::Won) => colors.win,
+            Some(DailyOutcome::Lost) | Some(DailyOutcome::Started) => colors.loss,
+            None => LIGHTGRAY,
+        }
+    }
+}
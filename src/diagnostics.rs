@@ -1,8 +1,49 @@
-use macroquad::{color::BLACK, text::draw_text, time::get_fps, window::screen_height};
+use std::collections::VecDeque;
 
+use macroquad::{
+    color::{Color, GREEN, RED, WHITE},
+    shapes::{draw_line, draw_rectangle},
+    text::{draw_text, measure_text},
+    time::{get_fps, get_frame_time, get_time},
+    window::screen_height,
+};
+
+use crate::{
+    game::{Game, GameState},
+    grid::ui_scale,
+    messages::FONT_SIZE,
+    mouse::cursor_position,
+};
+
+/// Frames kept for the graph of frame times.
+const FRAMES: usize = 120;
+/// Frame time at the top of the graph, twice that of 60 frames per second.
+const GRAPH_MAX: f32 = 1.0 / 30.0;
+const BACKGROUND: Color = Color::new(0.0, 0.0, 0.0, 0.7);
+
+/// Parts of a frame of the main loop that are timed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Input,
+    Update,
+    Draw,
+}
+
+/// An overlay of frame timings and of the state of the game, for finding what
+/// makes a board slow or a tile behave oddly.
+#[derive(Default)]
 pub struct Diagnostics {
     counter: i64,
     last_fps: i32,
+    visible: bool,
+    /// seconds of the last frames, the oldest first
+    frame_times: VecDeque<f32>,
+    /// when the phase being timed started
+    phase_start: f64,
+    /// seconds spent in each phase on this frame
+    phases: [f64; 3],
+    /// the same on the last frame
+    last_phases: [f64; 3],
 }
 
 impl Diagnostics {
@@ -10,21 +51,138 @@ impl Diagnostics {
         Diagnostics {
             counter: 0,
             last_fps: 0,
+            visible: false,
+            frame_times: VecDeque::with_capacity(FRAMES),
+            phase_start: 0.0,
+            phases: [0.0; 3],
+            last_phases: [0.0; 3],
         }
     }
 
-    pub fn on_loop(&mut self) {
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    /// Starts timing the phases of a frame.
+    pub fn start_frame(&mut self) {
+        self.phase_start = get_time();
+    }
+
+    /// Counts the time since the last call, or the start of the frame,
+    /// towards `phase`.
+    pub fn measure(&mut self, phase: Phase) {
+        let now = get_time();
+        self.phases[phase as usize] += now - self.phase_start;
+        self.phase_start = now;
+    }
+
+    pub fn on_loop(&mut self, game: &Game) {
         self.counter += 1;
         if self.counter % 50 == 0 {
             self.last_fps = get_fps();
         }
 
-        draw_text(
-            &format!("fps: {}", self.last_fps),
-            20.0,
-            screen_height() - 20.0,
-            20.0,
-            BLACK,
+        if self.frame_times.len() == FRAMES {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(get_frame_time());
+        self.last_phases = self.phases;
+        self.phases = [0.0; 3];
+
+        if self.visible {
+            self.draw(game);
+        }
+    }
+
+    fn draw(&self, game: &Game) {
+        let scale = ui_scale();
+        let font_size = FONT_SIZE * 0.8 * scale;
+        let line_height = font_size * 1.1;
+        let padding = 5.0 * scale;
+
+        let millis = |seconds: f64| seconds * 1000.0;
+        let [input, update, draw] = self.last_phases;
+        let frame = self.frame_times.back().copied().unwrap_or_default();
+        let mut lines = vec![
+            format!("fps: {}, frame {:.1} ms", self.last_fps, frame * 1000.0),
+            format!(
+                "input {:.2} ms, update {:.2} ms, draw {:.2} ms",
+                millis(input),
+                millis(update),
+                millis(draw)
+            ),
+        ];
+        if game.get_state() != GameState::NotStarted {
+            let dimensions = game.dimensions;
+            lines.push(format!(
+                "board {}x{}x{}, seed {}",
+                dimensions.x,
+                dimensions.y,
+                dimensions.z,
+                game.seed()
+            ));
+            lines.push(format!("tiles drawn: {}", game.tiles_drawn()));
+            if let Some((pos, tile)) = game.tile_at(cursor_position()) {
+                lines.push(format!(
+                    "row {}, col {}, layer {}: {:?}, {} around{}",
+                    pos.x,
+                    pos.y,
+                    pos.z,
+                    tile.state,
+                    tile.num_mines_around,
+                    match tile.has_mine {
+                        true => ", mine",
+                        false => "",
+                    }
+                ));
+            }
+        }
+
+        let graph_width = FRAMES as f32 * 2.0 * scale;
+        let graph_height = 40.0 * scale;
+        let text_width = lines
+            .iter()
+            .map(|line| measure_text(line, None, font_size as u16, 1.0).width)
+            .fold(graph_width, f32::max);
+        let width = text_width + padding * 2.0;
+        let height = lines.len() as f32 * line_height + graph_height + padding * 3.0;
+        let x = padding;
+        let y = screen_height() - height - padding;
+        draw_rectangle(x, y, width, height, BACKGROUND);
+
+        lines.iter().enumerate().for_each(|(i, line)| {
+            let line_y = y + padding + (i + 1) as f32 * line_height - line_height * 0.25;
+            draw_text(line, x + padding, line_y, font_size, WHITE);
+        });
+
+        // a bar per frame, red when slower than 60 frames per second
+        let graph_x = x + padding;
+        let graph_bottom = y + height - padding;
+        let bar_width = graph_width / FRAMES as f32;
+        self.frame_times.iter().enumerate().for_each(|(i, time)| {
+            let bar_height = (time / GRAPH_MAX).min(1.0) * graph_height;
+            let color = match *time > 1.0 / 59.0 {
+                true => RED,
+                false => GREEN,
+            };
+            draw_rectangle(
+                graph_x + i as f32 * bar_width,
+                graph_bottom - bar_height,
+                bar_width,
+                bar_height,
+                color,
+            );
+        });
+
+        // 60 frames per second
+        let target_y = graph_bottom - graph_height / 2.0;
+        draw_line(
+            graph_x,
+            target_y,
+            graph_x + graph_width,
+            target_y,
+            1.0,
+            Color { a: 0.5, ..WHITE },
         );
     }
 }
@@ -0,0 +1,96 @@
+//! Rates generated boards by letting the solver play them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bot::{BotAction, Solver, Technique, VisibleBoard, VisibleTile},
+    game::{Game, GameState},
+    tile::Tile,
+    vector3::Vector3,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DifficultyLevel {
+    Easy,
+    Medium,
+    Hard,
+    Evil,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Difficulty {
+    /// least clicks needed to clear the board
+    pub three_bv: i32,
+    /// guesses the solver was forced to make after the first click
+    pub guesses: i32,
+    /// hardest deduction the board needed, none when it was all guesses
+    pub technique: Option<Technique>,
+}
+
+impl Difficulty {
+    pub fn level(&self) -> DifficultyLevel {
+        match (self.guesses, self.technique) {
+            (0, None) | (0, Some(Technique::Single)) => DifficultyLevel::Easy,
+            (0, _) => DifficultyLevel::Medium,
+            (1, _) => DifficultyLevel::Hard,
+            _ => DifficultyLevel::Evil,
+        }
+    }
+}
+
+/// The guessed tile when it is safe, another hidden safe tile otherwise.
+pub(crate) fn safe_guess(
+    tiles: &[Tile],
+    board: &VisibleBoard,
+    pos: Vector3<i32>,
+) -> Option<Vector3<i32>> {
+    match tiles[board.index(pos)].has_mine {
+        false => Some(pos),
+        true => (0..tiles.len())
+            .find(|index| !tiles[*index].has_mine && board.tiles[*index] == VisibleTile::Hidden)
+            .map(|index| board.position(index)),
+    }
+}
+
+/// Plays the board with the solver. A guess that would hit a mine is counted
+/// and replaced by a safe tile, so the whole board gets rated.
+pub fn rate(dimensions: Vector3<i32>, tiles: &[Tile]) -> Difficulty {
+    let mut game = Game::headless();
+    game.start_with_tiles(dimensions, tiles.to_vec());
+
+    let mut difficulty = Difficulty {
+        three_bv: game.three_bv(),
+        guesses: 0,
+        technique: None,
+    };
+
+    let max_moves = tiles.len() * 2;
+    for _ in 0..max_moves {
+        if game.get_state() != GameState::Playing {
+            break;
+        }
+
+        let board = game.visible_board();
+        let (action, technique) = Solver::solve(&board);
+        difficulty.technique = difficulty.technique.max(technique);
+
+        match action {
+            BotAction::Reveal(pos) => game.reveal(pos),
+            BotAction::Flag(pos) => game.toggle_flag(pos),
+            BotAction::Guess(pos) => {
+                let first_click = board.tiles.iter().all(|tile| *tile == VisibleTile::Hidden);
+                if !first_click {
+                    difficulty.guesses += 1;
+                }
+
+                match safe_guess(tiles, &board, pos) {
+                    Some(pos) => game.reveal(pos),
+                    None => break,
+                }
+            }
+        }
+    }
+
+    difficulty
+}
@@ -0,0 +1,210 @@
+//! Live stream of what happens in a game, written as newline-delimited JSON
+//! for overlays, dashboards and commentary tools.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    net::TcpStream,
+    sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError},
+    thread,
+};
+
+use serde::Serialize;
+
+use crate::utils::current_time_millis;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum GameEvent {
+    Started {
+        seed: u64,
+        rows: i32,
+        cols: i32,
+        layers: i32,
+        mines: i32,
+    },
+    /// a hidden tile was clicked, `tiles` counts all the tiles it opened
+    Revealed {
+        row: i32,
+        col: i32,
+        layer: i32,
+        tiles: i32,
+        mine: bool,
+    },
+    Flagged {
+        row: i32,
+        col: i32,
+        layer: i32,
+    },
+    Unflagged {
+        row: i32,
+        col: i32,
+        layer: i32,
+    },
+    /// a revealed tile was clicked to open its neighbours
+    Chorded {
+        row: i32,
+        col: i32,
+        layer: i32,
+        tiles: i32,
+    },
+    Won {
+        seconds: i64,
+    },
+    Lost {
+        seconds: i64,
+    },
+}
+
+#[derive(Serialize)]
+struct EventLine<'a> {
+    /// unix time in milliseconds
+    time: i64,
+    #[serde(flatten)]
+    event: &'a GameEvent,
+}
+
+/// How many lines may wait for the writer before new events are dropped.
+const QUEUED_EVENTS: usize = 1024;
+
+enum Target {
+    Stdout,
+    File(String),
+    Tcp(String),
+    #[cfg(unix)]
+    Unix(String),
+}
+
+impl Target {
+    fn parse(target: &str) -> io::Result<Self> {
+        match target.split_once(':') {
+            _ if target == "stdout" => Ok(Target::Stdout),
+            Some(("file", path)) => Ok(Target::File(path.to_string())),
+            Some(("tcp", addr)) => Ok(Target::Tcp(addr.to_string())),
+            #[cfg(unix)]
+            Some(("unix", path)) => Ok(Target::Unix(path.to_string())),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unknown event stream '{}'", target),
+            )),
+        }
+    }
+
+    fn open(&self) -> io::Result<Box<dyn Write>> {
+        Ok(match self {
+            Target::Stdout => Box::new(io::stdout()),
+            Target::File(path) => Box::new(BufWriter::new(File::create(path)?)),
+            Target::Tcp(addr) => Box::new(TcpStream::connect(addr)?),
+            #[cfg(unix)]
+            Target::Unix(path) => Box::new(std::os::unix::net::UnixStream::connect(path)?),
+        })
+    }
+}
+
+/// Hands the events to a thread that writes them, so a slow reader never
+/// holds up the game. Events that don't fit in the queue are dropped.
+#[derive(Debug)]
+pub struct EventStream {
+    lines: SyncSender<String>,
+    dropping: bool,
+}
+
+impl EventStream {
+    /// Opens the stream described by `target`: `stdout`, `file:<path>`,
+    /// `tcp:<host:port>` or, on unix, `unix:<socket path>`. Only an unknown
+    /// target fails here, the writer thread reports when it can't connect.
+    pub fn open(target: &str) -> io::Result<Self> {
+        let name = target.to_string();
+        let target = Target::parse(target)?;
+        let (lines, receiver) = sync_channel(QUEUED_EVENTS);
+        thread::spawn(move || write_events(&name, target, receiver));
+
+        Ok(EventStream {
+            lines,
+            dropping: false,
+        })
+    }
+
+    /// Queues `event`, failing once the writer has given up.
+    pub fn emit(&mut self, event: &GameEvent) -> io::Result<()> {
+        let line = EventLine {
+            time: current_time_millis(),
+            event,
+        };
+        let mut line = serde_json::to_string(&line)?;
+        line.push('\n');
+
+        match self.lines.try_send(line) {
+            Ok(()) => self.dropping = false,
+            Err(TrySendError::Full(_)) => {
+                if !self.dropping {
+                    eprintln!("The event stream is falling behind, dropping events");
+                }
+                self.dropping = true;
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::BrokenPipe,
+                    "the event stream was closed",
+                ))
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Opens `target` and writes the lines until the game is done with them. A
+/// failure ends the thread, which the next emit notices.
+fn write_events(name: &str, target: Target, lines: Receiver<String>) {
+    let mut writer = match target.open() {
+        Ok(writer) => writer,
+        Err(err) => {
+            eprintln!("Could not open the event stream {}: {}", name, err);
+            return;
+        }
+    };
+
+    for line in lines {
+        if let Err(err) = writer
+            .write_all(line.as_bytes())
+            .and_then(|_| writer.flush())
+        {
+            eprintln!("Closing the event stream: {}", err);
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, time::Duration};
+
+    use super::*;
+
+    #[test]
+    fn events_are_written_in_the_background() {
+        assert!(EventStream::open("pigeon:coop").is_err());
+
+        let path = env::temp_dir().join(format!("minesweeper-events-{}.jsonl", std::process::id()));
+        let mut stream = EventStream::open(&format!("file:{}", path.display())).unwrap();
+        stream.emit(&GameEvent::Won { seconds: 3 }).unwrap();
+
+        let mut written = String::new();
+        for _ in 0..100 {
+            written = fs::read_to_string(&path).unwrap_or_default();
+            if !written.is_empty() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        assert!(
+            written.ends_with("\"event\":\"won\",\"seconds\":3}\n"),
+            "{}",
+            written
+        );
+
+        drop(stream);
+        let _ = fs::remove_file(&path);
+    }
+}
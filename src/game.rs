@@ -1,28 +1,169 @@
-use std::collections::VecDeque;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeSet, VecDeque},
+    env,
+    error::Error,
+    ffi::OsStr,
+    path::Path,
+};
 
-use rand::{rngs::ThreadRng, Rng};
+use chrono::NaiveDate;
+use macroquad::{
+    color::Color,
+    input::{is_mouse_button_down, MouseButton},
+    shapes::{draw_rectangle, draw_rectangle_lines},
+    window::{screen_height, screen_width},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
 use crate::{
-    game_textures::GameTextures,
-    grid::Grid,
-    messages::{write_game_over, write_remaining_mines, write_time, write_you_win},
+    animation::Animations,
+    board_cache::{BoardCache, DirtyTiles, TileLook},
+    board_text::{read_board, write_board},
+    bot::{BotAction, Solver, VisibleBoard, VisibleTile},
+    camera::Camera,
+    config::GameLevelConfig,
+    daily::{daily_seed, DailyChallenge, DailyResults},
+    difficulty::{rate, safe_guess, Difficulty, DifficultyLevel},
+    events::{EventStream, GameEvent},
+    grid::{Grid, Slot},
+    hint::{Hint, Pattern},
+    hot_seat::HotSeat,
+    hud::{
+        counter_width, draw_counter, draw_face, draw_progress, is_on_face,
+        write_three_bv_per_second, Face,
+    },
+    mbf,
+    messages::{
+        write_daily_label, write_difficulty, write_game_over, write_guess_needed, write_hint,
+        write_hot_seat_scores, write_hot_seat_turn, write_hot_seat_winner, write_layer_label,
+        write_puzzle_label, write_you_win,
+    },
+    mouse::cursor_position,
+    puzzle::{deducible, Puzzle, PuzzleProgress},
+    replay::{FormatError, Replay, ReplayAction, ReplayEvent},
+    rules::{ChordRule, Rules, WinRule},
+    settings::{Settings, TimerDisplay},
+    sound::{SoundEffect, Volume},
+    stats::Stats,
+    theme::{Theme, ThemeColors},
     tile::{Tile, TileState},
-    utils::current_time_seconds,
+    utils::{current_time_millis, current_time_seconds, current_time_string, get_time_diff},
     vector2::Vector2,
+    vector3::Vector3,
 };
 
-const NEIGHBORS: &[Vector2<i32>] = &[
-    Vector2::new(1, 0),
-    Vector2::new(-1, 0),
-    Vector2::new(0, 1),
-    Vector2::new(0, -1),
-    Vector2::new(1, 1),
-    Vector2::new(-1, -1),
-    Vector2::new(1, -1),
-    Vector2::new(-1, 1),
-];
-
-fn rand_num(from: i32, to: i32, rng: &mut ThreadRng, pred: impl Fn(i32) -> bool) -> usize {
+/// Offsets to all 26 cells of the surrounding 3x3x3 cube. On a single layer
+/// board the offsets that leave the layer are out of bounds, which leaves the
+/// classic 8 neighbours.
+pub(crate) const NEIGHBORS: [Vector3<i32>; 26] = neighbor_offsets();
+
+/// Empty columns drawn between two layers of the board.
+const LAYER_GAP: i32 = 1;
+
+const EXPORTS_DIR: &str = "./exports";
+
+/// Boards generated while looking for one of the difficulty of a level.
+const MAX_RATED_BOARDS: u64 = 100;
+/// Larger boards take too long for the solver to rate when they start.
+const MAX_RATED_TILES: usize = 5000;
+
+/// Boards generated while looking for a position to train a pattern on.
+const MAX_TRAINING_BOARDS: u64 = 200;
+/// Milliseconds of a frame spent looking for a training position.
+const TRAINING_SEARCH_MILLIS: i64 = 8;
+const TRAINING_SIZE: i32 = 16;
+const TRAINING_MINES: i32 = 50;
+
+const fn neighbor_offsets() -> [Vector3<i32>; 26] {
+    let mut offsets = [Vector3::new(0, 0, 0); 26];
+    let mut cell = 0;
+    let mut count = 0;
+
+    while cell < 27 {
+        // cell 13 is the centre of the cube, the tile itself
+        if cell != 13 {
+            offsets[count] = Vector3::new(cell % 3 - 1, cell / 3 % 3 - 1, cell / 9 - 1);
+            count += 1;
+        }
+        cell += 1;
+    }
+
+    offsets
+}
+
+type Board = (Vector3<i32>, Vec<Tile>);
+
+/// Loads a board in the format matching the extension of the file.
+fn load_board(path: &str) -> Result<Board, Box<dyn Error>> {
+    match Path::new(path).extension().and_then(OsStr::to_str) {
+        Some("mbf") => Ok(mbf::read_board(path)?),
+        _ => Ok(read_board(path)?),
+    }
+}
+
+fn generate_tiles(dimensions: Vector3<i32>, num_of_mines: i32, seed: u64) -> Vec<Tile> {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let tiles_count = dimensions.x * dimensions.y * dimensions.z;
+    let mut tiles: Vec<Tile> = vec![Default::default(); tiles_count as usize];
+
+    (0..num_of_mines).for_each(|_| {
+        let index = rand_num(0, tiles_count, &mut rng, |idx| {
+            !tiles[idx as usize].has_mine
+        });
+        tiles[index].has_mine = true;
+    });
+
+    tiles
+}
+
+/// A position of a generated board where the next certain move is named
+/// `pattern`, along with the index of the tile to resolve.
+fn training_board(pattern: Pattern, seed: u64) -> Option<(Vector3<i32>, Vec<Tile>, usize)> {
+    let dimensions = Vector3::new(TRAINING_SIZE, TRAINING_SIZE, 1);
+    let tiles = generate_tiles(dimensions, TRAINING_MINES, seed);
+
+    let mut game = Game::headless();
+    game.start_with_tiles(dimensions, tiles.clone());
+
+    while game.state == GameState::Playing {
+        let board = game.visible_board();
+        let action = match Hint::find(&board) {
+            Some(hint) if hint.pattern == pattern => {
+                return Some((dimensions, game.tiles, board.index(hint.target())));
+            }
+            Some(hint) => hint.action,
+            None => Solver::solve(&board).0,
+        };
+
+        match action {
+            BotAction::Reveal(pos) => game.reveal(pos),
+            BotAction::Flag(pos) => game.toggle_flag(pos),
+            BotAction::Guess(pos) => game.reveal(safe_guess(&tiles, &board, pos)?),
+        }
+    }
+
+    None
+}
+
+/// The first seed from `seed` on whose board is rated `target`.
+fn find_seed(level: &GameLevelConfig, target: DifficultyLevel, seed: u64) -> u64 {
+    let dimensions = Vector3::new(level.rows, level.cols, level.layers);
+
+    (0..MAX_RATED_BOARDS)
+        .map(|i| seed.wrapping_add(i))
+        .find(|seed| {
+            let tiles = generate_tiles(dimensions, level.mines, *seed);
+            rate(dimensions, &tiles).level() == target
+        })
+        .unwrap_or_else(|| {
+            eprintln!("No {:?} board found for {}", target, level.name);
+            seed
+        })
+}
+
+fn rand_num(from: i32, to: i32, rng: &mut StdRng, pred: impl Fn(i32) -> bool) -> usize {
     loop {
         let index = rng.gen_range(from..to);
         if pred(index) {
@@ -39,12 +180,57 @@ pub enum GameState {
     GameWon,
 }
 
+/// What a game was started from, to start another one like it.
+#[derive(Debug, Clone)]
+enum Origin {
+    Level(GameLevelConfig),
+    Daily(GameLevelConfig, NaiveDate),
+    HotSeat(GameLevelConfig, usize),
+    Puzzle(String),
+    /// with the seed the position was found from
+    Training(Pattern, u64),
+}
+
+/// Boards looked through for a training position, a few on every frame so
+/// the window keeps drawing.
+#[derive(Debug, Clone, Copy)]
+struct TrainingSearch {
+    pattern: Pattern,
+    first_seed: u64,
+    tried: u64,
+}
+
 #[derive(Debug)]
 pub struct Game {
-    pub dimensions: Vector2<i32>,
+    /// rows, cols and layers of the board
+    pub dimensions: Vector3<i32>,
     pub start_time: i64,
     pub end_time: i64,
     tiles: Vec<Tile>,
+    /// seed the mines of the board were generated from
+    seed: u64,
+    daily: Option<DailyChallenge>,
+    hot_seat: Option<HotSeat>,
+    puzzle: Option<Puzzle>,
+    /// shown until the next move
+    hint: Option<Hint>,
+    /// no certain move was found for the hint asked since the last move
+    guess_needed: bool,
+    training_search: Option<TrainingSearch>,
+    /// name of the level the board was started from, empty otherwise
+    level_name: String,
+    /// missing for replays and online races, which can't be started again
+    origin: Option<Origin>,
+    difficulty: Option<Difficulty>,
+    /// finished games are kept in the stats
+    record_stats: bool,
+    event_stream: Option<EventStream>,
+
+    start_millis: i64,
+    end_millis: i64,
+    replay_events: Vec<ReplayEvent>,
+    /// 3BV cleared so far, with the number of moves it was counted after
+    solved_three_bv: Cell<Option<(usize, i32)>>,
 
     initial_mines_count: i32,
     marked_mines_count: i32,
@@ -52,135 +238,960 @@ pub struct Game {
     state: GameState,
 
     grid: Grid,
-
-    textures: GameTextures,
+    camera: Camera,
+    animations: Animations,
+    /// tiles drawn on the last frame
+    tiles_drawn: Cell<usize>,
+    /// large boards drawn so far, see [`BoardCache`]
+    board_cache: RefCell<Option<BoardCache>>,
+    /// tiles to draw again into the cache
+    dirty_tiles: RefCell<DirtyTiles>,
+
+    /// missing when the game runs without a window
+    theme: Option<Theme>,
+    volume: Volume,
+    rules: Rules,
+    timer: TimerDisplay,
 }
 
 impl Game {
-    pub async fn new(grid: Grid) -> Game {
-        let textures = GameTextures::new().await;
+    pub async fn new(grid: Grid, settings: &Settings, default_theme: &Theme) -> Game {
+        let theme = Theme::from_settings(settings, default_theme).await;
+        let mut game = Game::with_theme(grid, Some(theme));
+        game.animations = Animations::new(settings.animations).await;
+        game.apply_settings(settings);
+        if let Some(theme) = &game.theme {
+            theme.sounds.start_music(&game.volume);
+        }
 
+        game
+    }
+
+    /// A game that can be played through its methods only, without a window.
+    pub fn headless() -> Game {
+        Game::with_theme(Grid::new(), None)
+    }
+
+    fn with_theme(grid: Grid, theme: Option<Theme>) -> Game {
         Game {
-            dimensions: Vector2::new(0, 0),
+            dimensions: Vector3::new(0, 0, 0),
             tiles: Vec::new(),
+            seed: 0,
+            daily: None,
+            hot_seat: None,
+            puzzle: None,
+            hint: None,
+            guess_needed: false,
+            training_search: None,
+            level_name: String::new(),
+            origin: None,
+            difficulty: None,
+            record_stats: theme.is_some(),
+            event_stream: None,
             start_time: 0,
             end_time: 0,
+            start_millis: 0,
+            end_millis: 0,
+            replay_events: Vec::new(),
+            solved_three_bv: Cell::new(None),
             initial_mines_count: 0,
             marked_mines_count: 0,
             grid,
+            camera: Camera::new(),
+            animations: Animations::default(),
+            tiles_drawn: Cell::new(0),
+            board_cache: RefCell::new(None),
+            dirty_tiles: RefCell::default(),
 
             state: GameState::NotStarted,
 
-            textures,
+            theme,
+            volume: Volume::default(),
+            rules: Rules::default(),
+            timer: TimerDisplay::Clock,
         }
     }
 
-    pub fn start(&mut self, rows: i32, cols: i32, num_of_mines: i32) {
-        self.dimensions = Vector2::new(rows, cols);
-        self.start_time = current_time_seconds();
-        self.state = GameState::Playing;
+    /// Moves the animations on by the time of the last frame, or to their end
+    /// when `skip` is set while they play.
+    pub fn update_animations(&mut self, skip: bool) {
+        self.animations.update();
+        if skip && self.animations.is_playing() {
+            self.animations.skip();
+        }
+        self.mark_animated_tiles();
+    }
 
-        let mut rng = rand::thread_rng();
+    /// Marks the tiles the animations show differently since the last call.
+    fn mark_animated_tiles(&mut self) {
+        let dirty = self.dirty_tiles.get_mut();
+        for index in self.animations.take_changed() {
+            dirty.mark(index);
+        }
+    }
 
-        self.tiles = vec![Default::default(); (rows * cols) as usize];
+    /// Marks the tiles the hint outlines, before and after it changes.
+    fn mark_hint_tiles(&self) {
+        let Some(hint) = &self.hint else {
+            return;
+        };
 
-        (0..num_of_mines).for_each(|_| {
-            let index = rand_num(0, rows * cols, &mut rng, |idx| {
-                !self.tiles[idx as usize].has_mine
-            });
-            self.tiles[index].has_mine = true;
+        let (BotAction::Reveal(target) | BotAction::Guess(target) | BotAction::Flag(target)) =
+            hint.action;
+        let mut dirty = self.dirty_tiles.borrow_mut();
+        for pos in hint.reasons.iter().chain([&target]) {
+            dirty.mark(self.get_index(*pos));
+        }
+    }
+
+    /// Switches the theme, and its music.
+    pub fn set_theme(&mut self, theme: Theme) {
+        if let Some(previous) = &self.theme {
+            previous.sounds.stop_music();
+        }
+        theme.sounds.start_music(&self.volume);
+        self.theme = Some(theme);
+        *self.board_cache.get_mut() = None;
+    }
+
+    pub fn set_volume(&mut self, volume: Volume) {
+        self.volume = volume;
+        if let Some(theme) = &self.theme {
+            theme.sounds.set_music_volume(&volume);
+        }
+    }
+
+    /// Takes up the settings apart from the theme, which [`Game::set_theme`]
+    /// loads. The rules apply from the next move.
+    pub fn apply_settings(&mut self, settings: &Settings) {
+        self.set_volume(settings.volume);
+        self.animations.set_enabled(settings.animations);
+        self.mark_animated_tiles();
+        self.rules = settings.rules;
+        self.timer = settings.timer;
+    }
+
+    pub fn background_color(&self) -> Color {
+        self.colors().background
+    }
+
+    pub fn colors(&self) -> ThemeColors {
+        self.theme
+            .as_ref()
+            .map_or(ThemeColors::default(), |theme| theme.colors)
+    }
+
+    pub fn start_level(&mut self, level: &GameLevelConfig) {
+        self.start_level_or_report(level, rand::random());
+    }
+
+    /// Starts the level, or reports why it can't be started and keeps the
+    /// game in play as it was.
+    fn start_level_or_report(&mut self, level: &GameLevelConfig, seed: u64) -> bool {
+        match self.start_level_with_seed(level, seed) {
+            Ok(()) => true,
+            Err(err) => {
+                eprintln!("Could not start {}: {}", level.name, err);
+                false
+            }
+        }
+    }
+
+    /// Fails without changing the game when the board of the level can't be
+    /// loaded.
+    pub fn start_level_with_seed(
+        &mut self,
+        level: &GameLevelConfig,
+        seed: u64,
+    ) -> Result<(), Box<dyn Error>> {
+        match &level.board {
+            Some(path) => match load_board(path) {
+                Ok((dimensions, tiles)) => self.start_with_tiles(dimensions, tiles),
+                Err(err) => {
+                    return Err(format!("could not load the board {}: {}", path, err).into())
+                }
+            },
+            None => {
+                let seed = match level.difficulty {
+                    Some(target) => find_seed(level, target, seed),
+                    None => seed,
+                };
+                self.start(level.rows, level.cols, level.layers, level.mines, seed);
+            }
+        }
+
+        self.level_name = level.name.clone();
+        self.origin = Some(Origin::Level(level.clone()));
+
+        Ok(())
+    }
+
+    /// Starts the board of the day, the same for everyone playing on `date`.
+    pub fn start_daily(&mut self, level: &GameLevelConfig, date: NaiveDate) {
+        if !self.start_level_or_report(level, daily_seed(date)) {
+            return;
+        }
+        self.daily = Some(DailyResults::start(date));
+        self.origin = Some(Origin::Daily(level.clone(), date));
+    }
+
+    /// Starts a board shared by players taking turns.
+    pub fn start_hot_seat(&mut self, level: &GameLevelConfig, players_count: usize) {
+        self.start_hot_seat_with_seed(level, players_count, rand::random());
+    }
+
+    fn start_hot_seat_with_seed(
+        &mut self,
+        level: &GameLevelConfig,
+        players_count: usize,
+        seed: u64,
+    ) {
+        if !self.start_level_or_report(level, seed) {
+            return;
+        }
+        self.hot_seat = Some(HotSeat::new(players_count));
+        self.origin = Some(Origin::HotSeat(level.clone(), players_count));
+    }
+
+    /// Starts a partially revealed board where everything that can be
+    /// deduced has to be resolved without guessing.
+    pub fn start_puzzle(&mut self, path: &str) {
+        let (dimensions, tiles) = match load_board(path) {
+            Ok(board) => board,
+            Err(err) => {
+                eprintln!("Could not load the puzzle {}: {}", path, err);
+                return;
+            }
+        };
+
+        let puzzle = Puzzle::new(path, deducible(dimensions, &tiles));
+        self.start_with_puzzle(dimensions, tiles, puzzle);
+        self.origin = Some(Origin::Puzzle(path.to_string()));
+    }
+
+    /// Starts looking for a position of a generated board where `pattern` is
+    /// the next certain move, to be resolved like a puzzle. See
+    /// [`Game::continue_training_search`].
+    pub fn start_training(&mut self, pattern: Pattern) {
+        self.training_search = Some(TrainingSearch {
+            pattern,
+            first_seed: rand::random(),
+            tried: 0,
         });
+    }
+
+    /// The pattern of the training position being looked for.
+    pub fn training_search(&self) -> Option<Pattern> {
+        self.training_search.map(|search| search.pattern)
+    }
+
+    pub fn cancel_training_search(&mut self) {
+        self.training_search = None;
+    }
+
+    /// Looks through boards for the training position for a part of a
+    /// frame, and starts it once found.
+    pub fn continue_training_search(&mut self) {
+        let Some(mut search) = self.training_search else {
+            return;
+        };
+
+        let started = current_time_millis();
+        while current_time_millis() - started < TRAINING_SEARCH_MILLIS {
+            if search.tried == MAX_TRAINING_BOARDS {
+                eprintln!("No {} position found to train on", search.pattern.name());
+                self.training_search = None;
+                return;
+            }
+
+            let seed = search.first_seed.wrapping_add(search.tried);
+            search.tried += 1;
+            if self.start_training_board(search.pattern, seed) {
+                return;
+            }
+        }
+
+        self.training_search = Some(search);
+    }
 
-        let initial_mines_count = self.tiles.iter().filter(|tile| tile.has_mine).count() as i32;
+    /// Starts the training position of the board generated from `seed`,
+    /// false when it has none.
+    fn start_training_board(&mut self, pattern: Pattern, seed: u64) -> bool {
+        let Some((dimensions, tiles, target)) = training_board(pattern, seed) else {
+            return false;
+        };
+
+        let puzzle = Puzzle::training(pattern.name(), BTreeSet::from([target]));
+        self.start_with_puzzle(dimensions, tiles, puzzle);
+        self.origin = Some(Origin::Training(pattern, seed));
+        true
+    }
 
-        self.initial_mines_count = initial_mines_count;
-        self.marked_mines_count = 0;
+    /// Replays and online races can't be started again.
+    pub fn can_start_again(&self) -> bool {
+        self.origin.is_some()
+    }
+
+    /// Starts the same board again, abandoning the game in play.
+    pub fn restart(&mut self) -> bool {
+        self.start_again(true)
+    }
+
+    /// Starts another game like the current one, on a new board when it is
+    /// generated, abandoning the game in play.
+    pub fn start_new_board(&mut self) -> bool {
+        self.start_again(false)
+    }
+
+    fn start_again(&mut self, same_board: bool) -> bool {
+        let Some(origin) = self.origin.clone() else {
+            return false;
+        };
+        // the daily board never changes, starting it again before the first
+        // move would only give up the ranking of the attempt
+        let ranked_daily = self.daily.is_some_and(|daily| daily.ranked);
+        if ranked_daily && self.state == GameState::Playing && !self.has_progress() {
+            return false;
+        }
+        self.abandon();
+
+        let seed = match same_board {
+            true => self.seed,
+            false => rand::random(),
+        };
+        match origin {
+            Origin::Level(level) => {
+                self.start_level_or_report(&level, seed);
+            }
+            Origin::Daily(level, date) => self.start_daily(&level, date),
+            Origin::HotSeat(level, players) => self.start_hot_seat_with_seed(&level, players, seed),
+            Origin::Puzzle(path) => self.start_puzzle(&path),
+            Origin::Training(pattern, training_seed) => match same_board {
+                true => {
+                    self.start_training_board(pattern, training_seed);
+                }
+                false => self.start_training(pattern),
+            },
+        }
+
+        true
+    }
+
+    /// Whether leaving the game would lose a move made.
+    pub fn has_progress(&self) -> bool {
+        self.state == GameState::Playing && !self.replay_events.is_empty()
+    }
+
+    /// Gives up the game in play, which counts as lost once a move was made.
+    pub fn abandon(&mut self) {
+        if self.has_progress() {
+            self.end(GameState::GameOver);
+        }
+    }
+
+    /// Abandons the game in play and goes back to the menu.
+    pub fn quit(&mut self) {
+        self.abandon();
+        self.state = GameState::NotStarted;
+        self.clear_hint();
+        self.training_search = None;
+        self.animations.clear();
+        self.dirty_tiles.get_mut().mark_all();
+    }
+
+    fn start_with_puzzle(&mut self, dimensions: Vector3<i32>, tiles: Vec<Tile>, puzzle: Puzzle) {
+        self.start_with_tiles(dimensions, tiles);
+        if self.state != GameState::Playing {
+            return;
+        }
+
+        let name = puzzle.name.clone();
+        self.puzzle = Some(puzzle);
+        if self.has_won() {
+            eprintln!("The puzzle {} has nothing to deduce", name);
+            self.end(GameState::GameWon);
+        }
+    }
+
+    pub fn start(&mut self, rows: i32, cols: i32, layers: i32, num_of_mines: i32, seed: u64) {
+        let dimensions = Vector3::new(rows, cols, layers);
+        let tiles = generate_tiles(dimensions, num_of_mines, seed);
+        let difficulty = (tiles.len() <= MAX_RATED_TILES).then(|| rate(dimensions, &tiles));
+
+        self.start_board(dimensions, tiles, seed);
+        self.difficulty = difficulty;
+    }
+
+    /// Starts a game on a prepared board. The tiles may already be revealed or
+    /// flagged, the numbers around them are recomputed.
+    pub fn start_with_tiles(&mut self, dimensions: Vector3<i32>, tiles: Vec<Tile>) {
+        self.start_board(dimensions, tiles, 0);
+    }
+
+    fn start_board(&mut self, dimensions: Vector3<i32>, tiles: Vec<Tile>, seed: u64) {
+        self.dimensions = dimensions;
+        self.tiles = tiles;
+        self.seed = seed;
+        self.daily = None;
+        self.hot_seat = None;
+        self.puzzle = None;
+        self.clear_hint();
+        self.training_search = None;
+        self.level_name.clear();
+        self.origin = None;
+        self.difficulty = None;
+        self.start_time = current_time_seconds();
+        self.start_millis = current_time_millis();
+        self.replay_events.clear();
+        self.solved_three_bv.set(None);
+        self.camera = Camera::new();
+        self.animations.clear();
+        self.dirty_tiles.get_mut().mark_all();
+        self.state = GameState::Playing;
+
+        self.initial_mines_count = self.tiles.iter().filter(|tile| tile.has_mine).count() as i32;
+        self.marked_mines_count = self
+            .tiles
+            .iter()
+            .filter(|tile| tile.state == TileState::Flagged)
+            .count() as i32;
         self.update_mines_count();
+
+        self.emit(GameEvent::Started {
+            seed,
+            rows: dimensions.x,
+            cols: dimensions.y,
+            layers: dimensions.z,
+            mines: self.initial_mines_count,
+        });
+
+        if self
+            .tiles
+            .iter()
+            .any(|tile| tile.has_mine && tile.state == TileState::Revealed)
+        {
+            self.end(GameState::GameOver);
+        } else if self.has_won() {
+            self.end(GameState::GameWon);
+        }
+    }
+
+    pub fn start_replay(&mut self, replay: &Replay) {
+        let mut tiles: Vec<Tile> = vec![Default::default(); (replay.rows * replay.cols) as usize];
+        for mine in &replay.mines {
+            tiles[(mine.x * replay.cols + mine.y) as usize].has_mine = true;
+        }
+
+        self.start_with_tiles(Vector3::new(replay.rows, replay.cols, 1), tiles);
+    }
+
+    /// The moves played so far, only single layer boards can be replayed.
+    pub fn replay(&self) -> Option<Replay> {
+        if self.state == GameState::NotStarted || self.dimensions.z != 1 {
+            return None;
+        }
+
+        let mines = self
+            .positions()
+            .filter(|pos| self.tiles[self.get_index(*pos)].has_mine)
+            .map(|pos| Vector2::new(pos.x, pos.y))
+            .collect();
+
+        Some(Replay {
+            rows: self.dimensions.x,
+            cols: self.dimensions.y,
+            mines,
+            events: self.replay_events.clone(),
+            player: env::var("USER").unwrap_or_default(),
+        })
+    }
+
+    /// Writes the current board to the exports directory, as text and as
+    /// `.mbf` when the format can hold it, and returns the written paths.
+    pub fn export_board(&self) -> Vec<String> {
+        if self.state == GameState::NotStarted {
+            return Vec::new();
+        }
+
+        let path = format!("{}/board-{}", EXPORTS_DIR, current_time_string());
+        let results = [
+            (
+                format!("{}.txt", path),
+                write_board(format!("{}.txt", path), self.dimensions, &self.tiles)
+                    .map_err(FormatError::from),
+            ),
+            (
+                format!("{}.mbf", path),
+                mbf::write_board(format!("{}.mbf", path), self.dimensions, &self.tiles),
+            ),
+        ];
+
+        Game::written_paths(results)
+    }
+
+    fn written_paths(results: [(String, Result<(), FormatError>); 2]) -> Vec<String> {
+        results
+            .into_iter()
+            .filter_map(|(path, result)| match result {
+                Ok(()) => Some(path),
+                Err(err) => {
+                    eprintln!("Could not export {}: {}", path, err);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn record(&mut self, action: ReplayAction, pos: Vector3<i32>) {
+        let time_millis = (current_time_millis() - self.start_millis).max(0) as u32;
+
+        self.replay_events.push(ReplayEvent {
+            time_millis,
+            action,
+            pos: Vector2::new(pos.x, pos.y),
+        });
     }
 
     pub fn end(&mut self, state: GameState) {
         self.state = state;
+        // flags look different once the game is over
+        self.dirty_tiles.get_mut().mark_all();
         self.end_time = current_time_seconds();
+        self.end_millis = current_time_millis();
+
+        let seconds = self.end_time - self.start_time;
+        if let Some(daily) = &self.daily {
+            DailyResults::finish(daily, state == GameState::GameWon, seconds);
+        }
+
+        if let Some(puzzle) = &self.puzzle {
+            if state == GameState::GameWon {
+                PuzzleProgress::finish(puzzle);
+            }
+        }
+
+        if self.record_stats && !self.level_name.is_empty() && self.hot_seat.is_none() {
+            let won = state == GameState::GameWon;
+            Stats::record(&self.level_name, self.seed, won, seconds, self.difficulty);
+        }
+
+        if state == GameState::GameWon {
+            // mines left hidden when the safe tiles are enough to win
+            for tile in &mut self.tiles {
+                if tile.has_mine && tile.state == TileState::Hidden {
+                    tile.state = TileState::Flagged;
+                    self.marked_mines_count += 1;
+                }
+            }
+            self.animations.celebrate();
+        }
+
+        self.emit(match state {
+            GameState::GameWon => GameEvent::Won { seconds },
+            _ => GameEvent::Lost { seconds },
+        });
+    }
+
+    /// Keeps the finished games in the stats, on by default with a window.
+    pub fn set_record_stats(&mut self, record: bool) {
+        self.record_stats = record;
+    }
+
+    /// Sends what happens in the game to `stream` from now on.
+    pub fn set_event_stream(&mut self, stream: EventStream) {
+        self.event_stream = Some(stream);
+    }
+
+    fn emit(&mut self, event: GameEvent) {
+        if let (Some(theme), Some(effect)) = (&self.theme, SoundEffect::for_event(&event)) {
+            theme.sounds.play(effect, &self.volume);
+        }
+
+        if let Some(stream) = &mut self.event_stream {
+            if let Err(err) = stream.emit(&event) {
+                eprintln!("Closing the event stream: {}", err);
+                self.event_stream = None;
+            }
+        }
     }
 
     pub fn make_move(&mut self, pos: Vector2<f32>) {
-        if self.state != GameState::Playing {
-            return;
+        if let Some((pos, _)) = self.resolve_tile_position(pos) {
+            self.reveal(pos);
         }
+    }
 
-        let (pos, index) = match self.resolve_tile_position(pos) {
-            Some(value) => value,
-            None => return,
-        };
+    /// Reveals a hidden tile, or chords around an already revealed one.
+    pub fn reveal(&mut self, pos: Vector3<i32>) {
+        if self.state != GameState::Playing || !self.within_bounds(pos) {
+            return;
+        }
 
-        let tile = &mut self.tiles[index];
+        let index = self.get_index(pos);
+        let tile = &self.tiles[index];
         if tile.state == TileState::Flagged || (tile.state == TileState::Revealed && tile.has_mine)
         {
             return;
         }
 
-        if tile.state == TileState::Hidden {
+        let revealed_before = self.revealed_count();
+        let hidden_before: Vec<usize> = match self.animations.is_enabled() {
+            true => (0..self.tiles.len())
+                .filter(|index| self.tiles[*index].state == TileState::Hidden)
+                .collect(),
+            false => Vec::new(),
+        };
+        let hit_mine;
+
+        let tile = &mut self.tiles[index];
+        let was_hidden = tile.state == TileState::Hidden;
+        if was_hidden {
             tile.state = TileState::Revealed;
+            self.dirty_tiles.get_mut().mark(index);
+            hit_mine = tile.has_mine;
+
+            if let Some(puzzle) = &mut self.puzzle {
+                puzzle.check_reveal(index, hit_mine);
+            }
 
-            if tile.has_mine {
-                println!("Game over!");
-                self.end(GameState::GameOver);
-            } else {
+            if !tile.has_mine {
                 self.clear_empty_neighbours(pos);
+            } else if self.hot_seat.is_some() || self.puzzle.is_some() {
+                // the mine stays on the board as a known one
+                self.marked_mines_count += 1;
             }
         } else {
-            self.click_on_shown_tile(pos);
+            hit_mine = self.click_on_shown_tile(pos);
         }
 
-        if self.has_won() {
+        let revealed = self.revealed_count() - revealed_before;
+        if !was_hidden && revealed == 0 && !hit_mine {
+            // a chord that opened nothing
+            return;
+        }
+        self.record(ReplayAction::Reveal, pos);
+        self.clear_hint();
+
+        let (row, col, layer) = (pos.x, pos.y, pos.z);
+        if was_hidden {
+            self.emit(GameEvent::Revealed {
+                row,
+                col,
+                layer,
+                tiles: revealed + hit_mine as i32,
+                mine: hit_mine,
+            });
+        } else if revealed > 0 {
+            self.emit(GameEvent::Chorded {
+                row,
+                col,
+                layer,
+                tiles: revealed,
+            });
+        }
+
+        if let Some(hot_seat) = &mut self.hot_seat {
+            hot_seat.end_turn(revealed, hit_mine);
+        }
+
+        let distance = |other: Vector3<i32>| {
+            (other.x - pos.x)
+                .abs()
+                .max((other.y - pos.y).abs())
+                .max((other.z - pos.z).abs())
+        };
+        let revealed_now: Vec<(usize, i32)> = hidden_before
+            .into_iter()
+            .filter(|index| self.tiles[*index].state == TileState::Revealed)
+            .map(|index| (index, distance(self.get_position(index))))
+            .collect();
+        self.animations.ripple(revealed_now);
+        self.mark_animated_tiles();
+
+        if hit_mine && self.hot_seat.is_none() && self.puzzle.is_none() {
+            self.end(GameState::GameOver);
+
+            let mines = self
+                .positions()
+                .map(|other| (other, self.get_index(other)))
+                .filter(|(_, index)| {
+                    let tile = &self.tiles[*index];
+                    tile.has_mine && tile.state != TileState::Flagged
+                })
+                .map(|(other, index)| (other, index, distance(other)))
+                .collect();
+            self.animations.explode(mines);
+            self.mark_animated_tiles();
+        } else if self.has_won() {
             self.end(GameState::GameWon);
         }
     }
 
+    /// Shows the next certain move and the pattern it follows from.
+    pub fn show_hint(&mut self) {
+        if self.state != GameState::Playing {
+            return;
+        }
+
+        self.mark_hint_tiles();
+        self.hint = Hint::find(&self.visible_board());
+        self.guess_needed = self.hint.is_none();
+        self.mark_hint_tiles();
+    }
+
+    fn clear_hint(&mut self) {
+        self.mark_hint_tiles();
+        self.hint = None;
+        self.guess_needed = false;
+    }
+
     pub fn has_won(&self) -> bool {
+        if let Some(puzzle) = &self.puzzle {
+            return puzzle.is_solved(&self.tiles);
+        }
+
+        // players taking turns only need to clear the safe tiles
         self.tiles.iter().all(|tile| match tile.has_mine {
-            true => tile.state == TileState::Flagged,
+            true => {
+                tile.state == TileState::Flagged
+                    || self.hot_seat.is_some()
+                    || self.rules.win == WinRule::RevealSafe
+            }
             false => tile.state == TileState::Revealed,
         })
     }
 
-    pub fn click_on_shown_tile(&mut self, pos: Vector2<i32>) {
-        let index = self.get_index(pos);
-        let tile = &mut self.tiles[index];
+    /// Percentage of the safe tiles revealed so far.
+    pub fn progress(&self) -> f32 {
+        let safe_tiles = self.tiles.len() as i32 - self.initial_mines_count;
+        if safe_tiles <= 0 {
+            return 100.0;
+        }
 
-        if tile.num_mines_around == 0 || !self.is_tile_cleared(pos) {
-            return;
+        (self.revealed_count() * 100 / safe_tiles) as f32
+    }
+
+    /// The board as a player sees it, without the hidden mines.
+    pub fn visible_board(&self) -> VisibleBoard {
+        let tiles = self
+            .tiles
+            .iter()
+            .map(|tile| match tile.state {
+                TileState::Hidden => VisibleTile::Hidden,
+                TileState::Flagged => VisibleTile::Flagged,
+                TileState::Revealed if tile.has_mine => VisibleTile::Mine,
+                TileState::Revealed => VisibleTile::Number(tile.num_mines_around),
+            })
+            .collect();
+
+        VisibleBoard {
+            dimensions: self.dimensions,
+            mines: self.initial_mines_count,
+            tiles,
+        }
+    }
+
+    pub fn difficulty(&self) -> Option<Difficulty> {
+        self.difficulty
+    }
+
+    /// Least clicks needed to clear the board: one per opening, the connected
+    /// empty tiles, and one per number that no opening reveals.
+    pub fn three_bv(&self) -> i32 {
+        self.three_bv_progress().0
+    }
+
+    /// 3BV cleared per second, counted again only after a move.
+    pub fn three_bv_per_second(&self) -> f32 {
+        let moves = self.replay_events.len();
+        let solved = match self.solved_three_bv.get() {
+            Some((counted_at, solved)) if counted_at == moves => solved,
+            _ => {
+                let (_, solved) = self.three_bv_progress();
+                self.solved_three_bv.set(Some((moves, solved)));
+                solved
+            }
+        };
+
+        let end_millis = match self.state {
+            GameState::Playing => current_time_millis(),
+            _ => self.end_millis,
+        };
+        solved as f32 * 1000.0 / (end_millis - self.start_millis).max(1) as f32
+    }
+
+    /// 3BV of the board and how much of it is revealed.
+    fn three_bv_progress(&self) -> (i32, i32) {
+        let mut opened = vec![false; self.tiles.len()];
+        let mut clicks = 0;
+        let mut solved = 0;
+
+        for pos in self.positions() {
+            let index = self.get_index(pos);
+            let tile = &self.tiles[index];
+            if tile.has_mine || tile.num_mines_around != 0 || opened[index] {
+                continue;
+            }
+
+            clicks += 1;
+            solved += (tile.state == TileState::Revealed) as i32;
+            opened[index] = true;
+            let mut q = VecDeque::from([pos]);
+            while let Some(pos) = q.pop_front() {
+                for neighbour_diff in &NEIGHBORS {
+                    let new_pos = pos.add(*neighbour_diff);
+                    if !self.within_bounds(new_pos) {
+                        continue;
+                    }
+
+                    let other_index = self.get_index(new_pos);
+                    if opened[other_index] {
+                        continue;
+                    }
+
+                    opened[other_index] = true;
+                    if self.tiles[other_index].num_mines_around == 0 {
+                        q.push_back(new_pos);
+                    }
+                }
+            }
         }
 
-        self.clear_empty_neighbours(pos);
+        let numbers = self
+            .tiles
+            .iter()
+            .zip(&opened)
+            .filter(|(tile, opened)| !tile.has_mine && !**opened);
+        let revealed = numbers
+            .clone()
+            .filter(|(tile, _)| tile.state == TileState::Revealed)
+            .count() as i32;
+
+        (clicks + numbers.count() as i32, solved + revealed)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    fn revealed_count(&self) -> i32 {
+        self.tiles
+            .iter()
+            .filter(|tile| !tile.has_mine && tile.state == TileState::Revealed)
+            .count() as i32
+    }
+
+    /// Chords around a revealed number as the rules say, true when it
+    /// revealed a mine.
+    pub fn click_on_shown_tile(&mut self, pos: Vector3<i32>) -> bool {
+        let index = self.get_index(pos);
+        let number = self.tiles[index].num_mines_around;
+        if number == 0 || self.tiles[index].has_mine {
+            return false;
+        }
+
+        match self.rules.chord {
+            ChordRule::Off => false,
+            ChordRule::Safe => {
+                if self.is_tile_cleared(pos) {
+                    self.clear_empty_neighbours(pos);
+                }
+                false
+            }
+            ChordRule::Classic => {
+                let neighbours: Vec<usize> = NEIGHBORS
+                    .iter()
+                    .map(|neighbour_diff| pos.add(*neighbour_diff))
+                    .filter(|pos| self.within_bounds(*pos))
+                    .map(|pos| self.get_index(pos))
+                    .collect();
+                let marked = neighbours
+                    .iter()
+                    .map(|index| &self.tiles[*index])
+                    .filter(|tile| {
+                        tile.state == TileState::Flagged
+                            || (tile.state == TileState::Revealed && tile.has_mine)
+                    })
+                    .count() as i32;
+                if marked != number {
+                    return false;
+                }
+
+                let mut hit_mine = false;
+                for index in neighbours {
+                    let tile = &mut self.tiles[index];
+                    if !tile.has_mine || tile.state != TileState::Hidden {
+                        continue;
+                    }
+
+                    tile.state = TileState::Revealed;
+                    self.dirty_tiles.get_mut().mark(index);
+                    hit_mine = true;
+                    if let Some(puzzle) = &mut self.puzzle {
+                        puzzle.check_reveal(index, true);
+                    }
+                    if self.hot_seat.is_some() || self.puzzle.is_some() {
+                        self.marked_mines_count += 1;
+                    }
+                }
+                self.clear_empty_neighbours(pos);
+
+                hit_mine
+            }
+        }
     }
 
     pub fn mark_tile(&mut self, pos: Vector2<f32>) {
+        if let Some((pos, _)) = self.resolve_tile_position(pos) {
+            self.toggle_flag(pos);
+        }
+    }
+
+    pub fn toggle_flag(&mut self, pos: Vector3<i32>) {
         if self.state != GameState::Playing {
             eprintln!("Game is not in playing state");
             return;
         }
 
-        let (_, index) = match self.resolve_tile_position(pos) {
-            Some(value) => value,
-            None => return,
-        };
+        if !self.within_bounds(pos) {
+            return;
+        }
 
-        let tile = &mut self.tiles[index];
-        if tile.state == TileState::Revealed {
+        let index = self.get_index(pos);
+        if self.tiles[index].state == TileState::Revealed {
             return;
         }
 
+        self.record(ReplayAction::Flag, pos);
+        self.clear_hint();
+
+        let tile = &mut self.tiles[index];
+
         tile.state = match tile.state {
             TileState::Flagged => TileState::Hidden,
             _ => TileState::Flagged,
         };
+        self.dirty_tiles.get_mut().mark(index);
 
-        self.marked_mines_count += match tile.state {
-            TileState::Flagged => 1,
-            _ => -1,
+        let flagged = tile.state == TileState::Flagged;
+        if let (Some(puzzle), true) = (&mut self.puzzle, flagged) {
+            puzzle.check_flag(tile.has_mine);
+        }
+        self.marked_mines_count += match flagged {
+            true => 1,
+            false => -1,
         };
 
+        let (row, col, layer) = (pos.x, pos.y, pos.z);
+        self.emit(match flagged {
+            true => GameEvent::Flagged { row, col, layer },
+            false => GameEvent::Unflagged { row, col, layer },
+        });
+
         if self.has_won() {
             self.end(GameState::GameWon);
         }
@@ -190,15 +1201,36 @@ impl Game {
         self.state
     }
 
-    fn resolve_tile_position(&mut self, pos: Vector2<f32>) -> Option<(Vector2<i32>, usize)> {
-        let tile_size = self.get_tile_size();
-        let transformed = pos.sub(self.grid.body.pos());
+    /// The tile under a point of the screen, with its position.
+    pub fn tile_at(&self, pos: Vector2<f32>) -> Option<(Vector3<i32>, &Tile)> {
+        self.resolve_tile_position(pos)
+            .map(|(pos, index)| (pos, &self.tiles[index]))
+    }
+
+    pub fn tiles_drawn(&self) -> usize {
+        self.tiles_drawn.get()
+    }
+
+    fn resolve_tile_position(&self, pos: Vector2<f32>) -> Option<(Vector3<i32>, usize)> {
+        let view = self.grid.body.screen_size();
+        let in_body = pos.sub(self.grid.body.pos());
+        if in_body.x < 0.0 || in_body.y < 0.0 || in_body.x >= view.x || in_body.y >= view.y {
+            return None;
+        }
+
+        let transformed = self.camera.to_board(in_body);
         if transformed.x < 0.0 || transformed.y < 0.0 {
             return None;
         }
 
-        let scaled = transformed.flip().scale(1.0 / tile_size);
-        let result = Vector2::new(scaled.x as i32, scaled.y as i32);
+        let scaled = transformed.flip().scale(1.0 / self.get_fit_tile_size());
+        let layer_width = self.dimensions.y + LAYER_GAP;
+        let col = scaled.y as i32;
+        if col % layer_width >= self.dimensions.y {
+            return None;
+        }
+
+        let result = Vector3::new(scaled.x as i32, col % layer_width, col / layer_width);
 
         if self.within_bounds(result) {
             Some((result, self.get_index(result)))
@@ -213,61 +1245,309 @@ impl Game {
         }
 
         self.draw_tiles();
+        self.draw_minimap();
+        self.animations
+            .draw_celebration(self.grid.body.pos(), self.grid.body.screen_size());
 
-        if self.state != GameState::NotStarted {
-            self.write_time();
-            self.write_remaining_mines();
+        self.draw_hud();
+        self.write_layer_labels();
+
+        let colors = self.colors();
+        if let Some(daily) = &self.daily {
+            write_daily_label(daily, colors.text, &self.grid.footer);
+        }
+
+        if let Some(hint) = &self.hint {
+            write_hint(hint, colors.hint, &self.grid.header);
+        } else if self.guess_needed {
+            write_guess_needed(colors.hint, &self.grid.header);
         }
 
-        match self.state {
-            GameState::GameOver => write_game_over(&self.grid.header),
-            GameState::GameWon => write_you_win(&self.grid.header),
+        if let Some(puzzle) = &self.puzzle {
+            write_puzzle_label(puzzle, colors.text, &self.grid.footer);
+        }
+
+        // the scores of a hot-seat game take both sides of the footer
+        match (&self.hot_seat, &self.difficulty) {
+            (Some(hot_seat), _) => write_hot_seat_scores(hot_seat, &colors, &self.grid.footer),
+            (None, Some(difficulty)) => {
+                write_difficulty(difficulty, colors.text, &self.grid.footer)
+            }
+            (None, None) => {}
+        }
+
+        match (self.state, &self.hot_seat) {
+            (GameState::GameOver, _) => write_game_over(colors.loss, &self.grid.header),
+            (GameState::GameWon, Some(hot_seat)) => {
+                write_hot_seat_winner(hot_seat, colors.win, &self.grid.header)
+            }
+            (GameState::GameWon, None) => write_you_win(colors.win, &self.grid.header),
+            (GameState::Playing, Some(hot_seat)) => {
+                write_hot_seat_turn(hot_seat, colors.hint, &self.grid.header)
+            }
             _ => {}
         }
     }
 
+    /// Draws large boards from their cache while they are not zoomed in past
+    /// its tiles, and the tiles in view otherwise.
     fn draw_tiles(&self) {
+        let Some(theme) = &self.theme else {
+            return;
+        };
         let tile_size = self.get_tile_size();
+        let cached_size = BoardCache::tile_size(self.board_cells(), self.tiles.len());
+
+        // kept up to date when zoomed in too, for the minimap
+        let mut drawn = match cached_size {
+            Some(cached_size) => self.update_board_cache(theme, cached_size),
+            None => {
+                self.board_cache.replace(None);
+                self.dirty_tiles.borrow_mut().take();
+                0
+            }
+        };
+        match (cached_size, &*self.board_cache.borrow()) {
+            (Some(cached_size), Some(cache)) if tile_size <= cached_size => {
+                let pos = self.get_tile_screen_pos(Vector3::new(0, 0, 0), tile_size);
+                cache.draw(pos, self.board_cells().scale(tile_size));
+            }
+            _ => {
+                let game_over = self.state == GameState::GameOver;
+                for pos in self.visible_positions(tile_size) {
+                    let screen_pos = self.get_tile_screen_pos(pos, tile_size);
+                    self.tile_look(pos, theme, game_over)
+                        .draw(screen_pos, tile_size - 1.0, theme);
+                    drawn += 1;
+                }
+            }
+        }
+        self.tiles_drawn.set(drawn);
 
-        let margin = self.grid.body.pos();
+        self.animations.draw_explosions(
+            |pos| self.get_tile_screen_pos(pos, tile_size),
+            tile_size - 1.0,
+        );
 
-        for i in 0..self.dimensions.x {
-            for j in 0..self.dimensions.y {
-                let pos = Vector2::new(i, j);
-                let index = self.get_index(pos);
-                let flipped_pos: Vector2<f32> = pos.flip().into();
+        if self.camera.is_zoomed() {
+            self.draw_margins(theme.colors.background);
+        }
+    }
 
-                self.tiles[index].draw(
-                    margin.add(flipped_pos.scale(tile_size)),
-                    tile_size - 1.0,
-                    &self.textures,
-                );
-            }
+    /// Draws the tiles that changed into the cache, making it first for a new
+    /// board or theme, and looks only at the dirty tiles once it is made.
+    /// Returns how many were drawn.
+    fn update_board_cache(&self, theme: &Theme, cached_size: f32) -> usize {
+        let cells = self.board_cells();
+        let dirty = self.dirty_tiles.borrow_mut().take();
+        let mut cache = self.board_cache.borrow_mut();
+        let new_cache = !matches!(&*cache, Some(cache) if cache.fits(cells, cached_size));
+        let cache = match &mut *cache {
+            Some(cache) if !new_cache => cache,
+            cache => cache.insert(BoardCache::new(
+                cells,
+                cached_size,
+                self.tiles.len(),
+                theme.colors.background,
+            )),
+        };
+
+        let game_over = self.state == GameState::GameOver;
+        let look = |pos: Vector3<i32>| {
+            let look = self.tile_look(pos, theme, game_over);
+            (self.get_index(pos), self.board_cell(pos), look)
+        };
+        match dirty.filter(|_| !new_cache) {
+            Some(dirty) => cache.update(
+                dirty
+                    .into_iter()
+                    .map(|index| look(self.get_position(index))),
+                theme,
+            ),
+            None => cache.update(self.positions().map(look), theme),
         }
     }
 
-    fn write_remaining_mines(&self) {
-        write_remaining_mines(
-            self.initial_mines_count - self.marked_mines_count,
-            &self.grid.header,
+    /// The tile as shown by the animations, outlined when part of the hint.
+    fn tile_look(&self, pos: Vector3<i32>, theme: &Theme, game_over: bool) -> TileLook {
+        let outline = self.hint.as_ref().and_then(|hint| match hint.action {
+            BotAction::Flag(target) if target == pos => Some(theme.colors.loss),
+            BotAction::Reveal(target) if target == pos => Some(theme.colors.win),
+            _ => hint.reasons.contains(&pos).then_some(theme.colors.hint),
+        });
+
+        let index = self.get_index(pos);
+        let tile = &self.tiles[index];
+        let tile = Tile {
+            state: self.animations.shown_state(index, tile.state),
+            ..tile.clone()
+        };
+
+        TileLook::new(tile, outline, game_over)
+    }
+
+    /// Positions of the tiles at least partly in the body, found from the
+    /// view rather than by going through the whole board.
+    fn visible_positions(&self, tile_size: f32) -> impl Iterator<Item = Vector3<i32>> {
+        let cells = self.board_cells();
+        let view = self.grid.body.screen_size();
+        let start = self.camera.offset.scale(-1.0 / tile_size);
+        let end = view.sub(self.camera.offset).scale(1.0 / tile_size);
+
+        let cols = (start.x.floor().max(0.0) as i32)..(end.x.ceil().min(cells.x) as i32);
+        let rows = (start.y.floor().max(0.0) as i32)..(end.y.ceil().min(cells.y) as i32);
+        let board_cols = self.dimensions.y;
+        let layer_width = board_cols + LAYER_GAP;
+
+        rows.flat_map(move |row| {
+            cols.clone().filter_map(move |cell| {
+                let col = cell % layer_width;
+                (col < board_cols).then(|| Vector3::new(row, col, cell / layer_width))
+            })
+        })
+    }
+
+    /// Covers the tiles sticking out of the body when zoomed in.
+    fn draw_margins(&self, color: Color) {
+        let body_pos = self.grid.body.pos();
+        let body_end = body_pos.add(self.grid.body.screen_size());
+        let (width, height) = (screen_width(), screen_height());
+
+        draw_rectangle(0.0, 0.0, width, body_pos.y, color);
+        draw_rectangle(0.0, body_end.y, width, height - body_end.y, color);
+        draw_rectangle(0.0, 0.0, body_pos.x, height, color);
+        draw_rectangle(body_end.x, 0.0, width - body_end.x, height, color);
+    }
+
+    /// Draws the revealed and flagged tiles of the whole board small, or the
+    /// board from its cache, and the part of it in view.
+    fn draw_minimap(&self) {
+        let (Some(theme), Some((pos, scale))) = (&self.theme, self.minimap()) else {
+            return;
+        };
+
+        let tile_size = self.get_fit_tile_size() * scale;
+        let board = self.board_cells().scale(tile_size);
+        match &*self.board_cache.borrow() {
+            Some(cache) => cache.draw(pos, board),
+            None => self.draw_minimap_tiles(theme, pos, tile_size),
+        }
+
+        let view_pos = self
+            .camera
+            .to_board(Vector2::new(0.0, 0.0))
+            .scale(scale)
+            .add(pos);
+        let view_size = self.grid.body.screen_size().scale(scale / self.camera.zoom);
+        draw_rectangle_lines(
+            view_pos.x,
+            view_pos.y,
+            view_size.x,
+            view_size.y,
+            2.0,
+            theme.colors.text,
         );
     }
 
-    fn write_time(&self) {
+    fn draw_minimap_tiles(&self, theme: &Theme, pos: Vector2<f32>, tile_size: f32) {
+        let board = self.board_cells().scale(tile_size);
+        draw_rectangle(pos.x, pos.y, board.x, board.y, theme.colors.hidden);
+
+        for tile_pos in self.positions() {
+            let tile = &self.tiles[self.get_index(tile_pos)];
+            let color = match tile.state {
+                TileState::Hidden => continue,
+                TileState::Flagged => theme.colors.flag_background,
+                TileState::Revealed if tile.has_mine => theme.colors.mine_background,
+                TileState::Revealed => theme.colors.revealed,
+            };
+
+            let cell_pos = pos.add(self.board_cell(tile_pos).scale(tile_size));
+            draw_rectangle(cell_pos.x, cell_pos.y, tile_size, tile_size, color);
+        }
+    }
+
+    fn write_layer_labels(&self) {
+        if self.dimensions.z < 2 {
+            return;
+        }
+
+        let text = self.colors().text;
+        let tile_size = self.get_tile_size();
+        let body_pos = self.grid.body.pos();
+        let body_end = body_pos.add(self.grid.body.screen_size());
+        for layer in 0..self.dimensions.z {
+            let pos = self.get_tile_screen_pos(Vector3::new(0, 0, layer), tile_size);
+            if pos.x >= body_pos.x && pos.x < body_end.x {
+                write_layer_label(layer, pos.x, text, &self.grid.footer);
+            }
+        }
+    }
+
+    fn draw_hud(&self) {
+        let header = &self.grid.header;
+        let pos = header.pos();
+        let size = header.screen_size();
+
+        let remaining = (self.initial_mines_count - self.marked_mines_count).clamp(-99, 999);
+        draw_counter(&format!("{:03}", remaining), pos, size.y);
+
+        let mut right = pos.x + size.x;
+        if let Some(time) = self.timer_text() {
+            right -= counter_width(&time, size.y);
+            draw_counter(&time, Vector2::new(right, pos.y), size.y);
+            right -= size.y / 4.0;
+        }
+        if self.state == GameState::Playing || self.state == GameState::GameWon {
+            write_three_bv_per_second(
+                self.three_bv_per_second(),
+                right,
+                self.colors().text,
+                header,
+            );
+        }
+
+        let pressed = is_mouse_button_down(MouseButton::Left);
+        let on_face = is_on_face(cursor_position(), header);
+        let face = match self.state {
+            GameState::GameWon => Face::Won,
+            GameState::GameOver => Face::Lost,
+            _ if pressed && !on_face => Face::Surprised,
+            _ => Face::Smiling,
+        };
+        draw_face(face, pressed && on_face, header);
+
+        draw_progress(self.progress() / 100.0, header);
+    }
+
+    /// Time on the counter as set in the settings, none when hidden.
+    fn timer_text(&self) -> Option<String> {
         let end_time = match self.state {
             GameState::GameOver | GameState::GameWon => self.end_time,
             _ => current_time_seconds(),
         };
 
-        write_time(self.start_time, end_time, &self.grid.header);
+        match self.timer {
+            TimerDisplay::Clock => {
+                let (mins, secs) = get_time_diff(self.start_time, end_time);
+                Some(format!("{:02}:{:02}", mins, secs))
+            }
+            TimerDisplay::Seconds => Some(format!("{:03}", end_time - self.start_time)),
+            TimerDisplay::Hidden => None,
+        }
+    }
+
+    /// Whether `pos` is on the face that restarts the board.
+    pub fn is_on_face(&self, pos: Vector2<f32>) -> bool {
+        self.state != GameState::NotStarted && is_on_face(pos, &self.grid.header)
     }
 
-    fn clear_empty_neighbours(&mut self, pos: Vector2<i32>) {
-        let mut q: VecDeque<Vector2<i32>> = VecDeque::new();
+    fn clear_empty_neighbours(&mut self, pos: Vector3<i32>) {
+        let mut q: VecDeque<Vector3<i32>> = VecDeque::new();
         q.push_back(pos);
 
         while let Some(pos) = q.pop_front() {
-            for neighbour_diff in NEIGHBORS {
+            for neighbour_diff in &NEIGHBORS {
                 let new_pos = pos.add(*neighbour_diff);
 
                 if !self.within_bounds(new_pos) {
@@ -282,6 +1562,7 @@ impl Game {
                 }
 
                 other_tile.state = TileState::Revealed;
+                self.dirty_tiles.get_mut().mark(other_tile_index);
 
                 if other_tile.num_mines_around == 0 {
                     q.push_back(new_pos);
@@ -291,17 +1572,26 @@ impl Game {
     }
 
     fn update_mines_count(&mut self) {
-        for i in 0..self.dimensions.x {
-            for j in 0..self.dimensions.y {
-                let pos = Vector2::new(i, j);
-                let count = self.count_mines_around(pos);
-                let index = self.get_index(pos);
-                self.tiles[index].update_num_mines_around(count);
-            }
+        for pos in self.positions() {
+            let count = self.count_mines_around(pos);
+            let index = self.get_index(pos);
+            self.tiles[index].update_num_mines_around(count);
         }
     }
 
-    fn count_mines_around(&self, pos: Vector2<i32>) -> i32 {
+    fn positions(&self) -> impl Iterator<Item = Vector3<i32>> {
+        let Vector3 {
+            x: rows,
+            y: cols,
+            z: layers,
+        } = self.dimensions;
+
+        (0..layers).flat_map(move |layer| {
+            (0..rows).flat_map(move |row| (0..cols).map(move |col| Vector3::new(row, col, layer)))
+        })
+    }
+
+    fn count_mines_around(&self, pos: Vector3<i32>) -> i32 {
         NEIGHBORS
             .iter()
             .map(|neighbour_diff| pos.add(*neighbour_diff))
@@ -309,7 +1599,7 @@ impl Game {
             .count() as i32
     }
 
-    fn is_tile_cleared(&self, pos: Vector2<i32>) -> bool {
+    fn is_tile_cleared(&self, pos: Vector3<i32>) -> bool {
         let index = self.get_index(pos);
         if self.tiles[index].has_mine {
             return false;
@@ -333,19 +1623,338 @@ impl Game {
         self.tiles[index].num_mines_around == count
     }
 
-    fn get_tile_size(&self) -> f32 {
+    /// Columns and rows of tiles the board takes, with the gaps between layers.
+    fn board_cells(&self) -> Vector2<f32> {
+        // layers are laid out side by side, separated by a gap
+        let layers = self.dimensions.z;
+        let board_cols = layers * self.dimensions.y + (layers - 1) * LAYER_GAP;
+
+        Vector2::new(board_cols, self.dimensions.x).into()
+    }
+
+    /// Size of the tiles for the whole board to fit the body.
+    fn get_fit_tile_size(&self) -> f32 {
         self.grid
             .body
             .screen_size()
-            .div(self.dimensions.flip().into())
+            .div(self.board_cells())
             .min_component()
     }
 
-    fn within_bounds(&self, coord: Vector2<i32>) -> bool {
-        coord.x >= 0 && coord.y >= 0 && coord.x < self.dimensions.x && coord.y < self.dimensions.y
+    fn get_tile_size(&self) -> f32 {
+        self.get_fit_tile_size() * self.camera.zoom
+    }
+
+    /// Column and row of the tile among the cells of [`Game::board_cells`].
+    fn board_cell(&self, pos: Vector3<i32>) -> Vector2<f32> {
+        let col = pos.z * (self.dimensions.y + LAYER_GAP) + pos.y;
+
+        Vector2::new(col, pos.x).into()
+    }
+
+    fn get_tile_screen_pos(&self, pos: Vector3<i32>, tile_size: f32) -> Vector2<f32> {
+        self.grid
+            .body
+            .pos()
+            .add(self.camera.offset)
+            .add(self.board_cell(pos).scale(tile_size))
+    }
+
+    /// Zooms and pans the board, see [`Camera::handle_input`], or moves the
+    /// view to a spot clicked on the minimap.
+    pub fn update_camera(&mut self, cursor: Vector2<f32>, clicked: bool) {
+        if self.state == GameState::NotStarted {
+            return;
+        }
+
+        let board = self.board_cells().scale(self.get_fit_tile_size());
+        let view = self.grid.body.screen_size();
+        self.camera
+            .handle_input(cursor.sub(self.grid.body.pos()), board, view);
+
+        if let Some((minimap_pos, scale)) = self.minimap().filter(|_| clicked) {
+            let on_minimap = cursor.sub(minimap_pos);
+            let minimap_size = board.scale(scale);
+            if on_minimap.x >= 0.0
+                && on_minimap.y >= 0.0
+                && on_minimap.x <= minimap_size.x
+                && on_minimap.y <= minimap_size.y
+            {
+                self.camera.center_on(on_minimap.scale(1.0 / scale), view);
+                self.camera.clamp(board, view);
+            }
+        }
+    }
+
+    /// Screen position of the minimap in the middle of the footer, and its
+    /// size relative to the board at zoom 1. Only shown while zoomed in.
+    fn minimap(&self) -> Option<(Vector2<f32>, f32)> {
+        if !self.camera.is_zoomed() {
+            return None;
+        }
+
+        let footer = &self.grid.footer;
+        let board = self.board_cells();
+        let (x, slot_width) = footer.slot(Slot::Middle);
+        let height = footer.screen_size().y;
+        let scale = (height / board.y).min(slot_width / board.x) / self.get_fit_tile_size();
+        let width = board.x * self.get_fit_tile_size() * scale;
+        let pos = Vector2::new(x + (slot_width - width) / 2.0, footer.pos().y);
+
+        Some((pos, scale))
+    }
+
+    fn within_bounds(&self, coord: Vector3<i32>) -> bool {
+        coord.x >= 0
+            && coord.y >= 0
+            && coord.z >= 0
+            && coord.x < self.dimensions.x
+            && coord.y < self.dimensions.y
+            && coord.z < self.dimensions.z
     }
 
-    fn get_index(&self, pos: Vector2<i32>) -> usize {
-        (pos.x as usize * self.dimensions.y as usize) + pos.y as usize
+    fn get_index(&self, pos: Vector3<i32>) -> usize {
+        let layer_start = pos.z as usize * self.dimensions.x as usize;
+        (layer_start + pos.x as usize) * self.dimensions.y as usize + pos.y as usize
+    }
+
+    fn get_position(&self, index: usize) -> Vector3<i32> {
+        let index = index as i32;
+        let Vector3 {
+            x: rows, y: cols, ..
+        } = self.dimensions;
+
+        Vector3::new(index / cols % rows, index % cols, index / (cols * rows))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::{board_text::from_text, config::Config};
+
+    const ONE_TWO_ONE: &str = "boards/one-two-one.txt";
+
+    fn game_from_text(text: &str) -> Game {
+        let (dimensions, tiles) = from_text(text).unwrap();
+        let mut game = Game::headless();
+        game.start_with_tiles(dimensions, tiles);
+        game
+    }
+
+    fn level_with_board(board: &str) -> GameLevelConfig {
+        GameLevelConfig {
+            name: board.to_string(),
+            rows: 0,
+            cols: 0,
+            layers: 1,
+            mines: 0,
+            board: Some(board.to_string()),
+            difficulty: None,
+        }
+    }
+
+    fn shown(game: &Game, row: i32, col: i32) -> VisibleTile {
+        game.visible_board().get(Vector3::new(row, col, 0))
+    }
+
+    #[test]
+    fn moves_mark_only_the_tiles_they_change() {
+        let mut game = game_from_text(".*..\n**..\n....\n....\n");
+        let dirty = |game: &Game| game.dirty_tiles.borrow_mut().take();
+        assert_eq!(dirty(&game), None);
+
+        game.toggle_flag(Vector3::new(1, 1, 0));
+        assert_eq!(dirty(&game), Some(BTreeSet::from([5])));
+
+        game.reveal(Vector3::new(3, 3, 0));
+        let revealed: BTreeSet<usize> = (0..game.tiles.len())
+            .filter(|index| game.tiles[*index].state == TileState::Revealed)
+            .collect();
+        assert_eq!(revealed.len(), 12);
+        assert_eq!(dirty(&game), Some(revealed));
+
+        game.reveal(Vector3::new(0, 1, 0));
+        assert_eq!(dirty(&game), None);
+    }
+
+    #[test]
+    fn fixture_numbers_match_the_mines() {
+        for entry in fs::read_dir("boards").unwrap() {
+            let path = entry.unwrap().path();
+            let text = fs::read_to_string(&path).unwrap();
+            let game = game_from_text(&text);
+            let board = game.visible_board();
+
+            let mut pos = Vector3::new(0, 0, 0);
+            for line in text.lines().map(str::trim) {
+                if line.starts_with(';') {
+                    continue;
+                }
+                if line.is_empty() {
+                    if pos.x > 0 {
+                        pos = Vector3::new(0, 0, pos.z + 1);
+                    }
+                    continue;
+                }
+
+                for (col, tile) in line.chars().enumerate() {
+                    if let Some(number) = tile.to_digit(10) {
+                        let tile_pos = Vector3::new(pos.x, col as i32, pos.z);
+                        assert_eq!(
+                            board.get(tile_pos),
+                            VisibleTile::Number(number as i32),
+                            "{:?} at {:?}",
+                            path,
+                            tile_pos
+                        );
+                    }
+                }
+                pos.x += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn reveal_floods_empty_tiles() {
+        let mut game = game_from_text("....\n....\n...*\n");
+
+        game.reveal(Vector3::new(0, 0, 0));
+        assert_eq!(shown(&game, 0, 0), VisibleTile::Number(0));
+        assert_eq!(shown(&game, 2, 2), VisibleTile::Number(1));
+        assert_eq!(shown(&game, 2, 3), VisibleTile::Hidden);
+        assert_eq!(game.get_state(), GameState::Playing);
+
+        game.toggle_flag(Vector3::new(2, 3, 0));
+        assert_eq!(game.get_state(), GameState::GameWon);
+    }
+
+    #[test]
+    fn revealing_a_mine_loses() {
+        let mut game = game_from_text("..\n.*\n");
+        game.reveal(Vector3::new(1, 1, 0));
+        assert_eq!(game.get_state(), GameState::GameOver);
+    }
+
+    #[test]
+    fn safe_chord_needs_the_right_flags() {
+        let mut game = Game::headless();
+        game.start_level_with_seed(&level_with_board(ONE_TWO_ONE), 0)
+            .unwrap();
+
+        // the 1 next to the mine on the right of the 1-2-1
+        game.reveal(Vector3::new(1, 7, 0));
+        assert_eq!(shown(&game, 0, 6), VisibleTile::Hidden);
+
+        game.toggle_flag(Vector3::new(1, 6, 0));
+        game.reveal(Vector3::new(1, 7, 0));
+        assert_eq!(shown(&game, 0, 6), VisibleTile::Number(1));
+        assert_eq!(game.get_state(), GameState::Playing);
+    }
+
+    #[test]
+    fn classic_chord_on_a_wrong_flag_loses() {
+        let mut game = Game::headless();
+        game.rules.chord = ChordRule::Classic;
+        game.start_level_with_seed(&level_with_board(ONE_TWO_ONE), 0)
+            .unwrap();
+
+        game.toggle_flag(Vector3::new(0, 6, 0));
+        game.reveal(Vector3::new(1, 7, 0));
+        assert_eq!(shown(&game, 1, 6), VisibleTile::Mine);
+        assert_eq!(game.get_state(), GameState::GameOver);
+    }
+
+    #[test]
+    fn a_hint_without_a_certain_move_asks_for_a_guess() {
+        let mut game = game_from_text("...\n.*.\n...\n");
+        game.show_hint();
+        assert!(game.hint.is_none() && game.guess_needed);
+
+        game.reveal(Vector3::new(0, 0, 0));
+        assert!(!game.guess_needed);
+    }
+
+    #[test]
+    fn training_positions_are_found_over_several_calls() {
+        let mut game = Game::headless();
+        game.start_training(Pattern::Single);
+        assert_eq!(game.training_search(), Some(Pattern::Single));
+
+        let mut calls = 0;
+        while game.training_search().is_some() {
+            game.continue_training_search();
+            calls += 1;
+            assert!(calls < 10_000);
+        }
+        assert_eq!(game.get_state(), GameState::Playing);
+        assert!(matches!(
+            game.origin,
+            Some(Origin::Training(Pattern::Single, _))
+        ));
+    }
+
+    #[test]
+    fn a_board_that_fails_to_load_keeps_the_game() {
+        let mut game = Game::headless();
+        game.start_level_with_seed(&level_with_board(ONE_TWO_ONE), 0)
+            .unwrap();
+        game.reveal(Vector3::new(0, 6, 0));
+
+        let level = level_with_board("boards/missing.txt");
+        assert!(game.start_level_with_seed(&level, 0).is_err());
+        assert_eq!(game.level_name, ONE_TWO_ONE);
+        assert!(matches!(&game.origin, Some(Origin::Level(level)) if level.name == ONE_TWO_ONE));
+        assert!(game.has_progress());
+    }
+
+    #[test]
+    fn restarting_a_daily_before_a_move_keeps_it_ranked() {
+        let config = Config::new();
+        let date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        let mut game = Game::headless();
+        // what start_daily does, without touching the saved results
+        game.start_level_with_seed(&config.daily, daily_seed(date))
+            .unwrap();
+        game.daily = Some(DailyChallenge { date, ranked: true });
+        game.origin = Some(Origin::Daily(config.daily.clone(), date));
+        let tiles = game.tiles.clone();
+
+        assert!(!game.restart());
+        assert!(!game.start_new_board());
+        assert_eq!(game.daily, Some(DailyChallenge { date, ranked: true }));
+        assert_eq!(game.seed, daily_seed(date));
+        assert_eq!(game.tiles, tiles);
+        assert_eq!(game.state, GameState::Playing);
+    }
+
+    #[test]
+    fn clicks_that_change_nothing_are_not_recorded() {
+        let mut game = game_from_text("F..\n...\n..*\n");
+
+        game.reveal(Vector3::new(0, 0, 0));
+        assert!(!game.has_progress());
+        assert!(game.replay().unwrap().events.is_empty());
+
+        game.reveal(Vector3::new(1, 1, 0));
+        assert!(game.has_progress());
+        assert_eq!(game.replay().unwrap().events.len(), 1);
+    }
+
+    #[test]
+    fn clicks_on_revealed_tiles_are_not_recorded() {
+        // the 1 at (0, 1) has its mine hidden, so chording it opens nothing
+        let mut game = game_from_text("*1.\n11.\n...\n");
+
+        game.toggle_flag(Vector3::new(0, 1, 0));
+        game.reveal(Vector3::new(0, 1, 0));
+        game.reveal(Vector3::new(1, 1, 0));
+        assert!(game.replay_events.is_empty());
+        assert!(!game.has_progress());
+
+        game.toggle_flag(Vector3::new(0, 0, 0));
+        assert_eq!(game.replay_events.len(), 1);
     }
 }
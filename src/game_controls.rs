@@ -1,73 +1,183 @@
 use macroquad::{
-    math::vec2,
-    ui::{root_ui, widgets},
+    color::Color,
+    math::{vec2, RectOffset},
+    ui::{root_ui, widgets, Skin, Ui},
     window::{screen_height, screen_width},
 };
 
-use crate::{config::Config, vector2::Vector2};
+use crate::{config::Config, grid::ui_scale, hint::Pattern, vector2::Vector2};
 
+/// Sizes of the buttons before scaling.
 pub const WIDTH: f32 = 190.0;
 pub const HEIGHT: f32 = 30.0;
 pub const PADDING: f32 = 10.0;
+const FONT_SIZE: f32 = 16.0;
 
-type ButtonLabel = String;
-type ButtonIndex = usize;
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MenuAction {
+    /// index of the level in the config
+    Level(usize),
+    Daily,
+    DailyCalendar,
+    /// number of players taking turns
+    HotSeat(usize),
+    Online,
+    /// index of the puzzle pack in the config
+    PuzzlePack(usize),
+    Training(Pattern),
+    Settings,
+}
 
 #[derive(Debug)]
 pub struct GameControls {
-    buttons: Vec<String>,
+    buttons: Vec<(String, MenuAction)>,
 }
 
 impl GameControls {
     pub fn new(config: &Config) -> Self {
-        let buttons = config
+        let mut buttons: Vec<(String, MenuAction)> = config
             .levels
             .iter()
-            .map(|level| level.name.clone())
+            .enumerate()
+            .map(|(index, level)| (level.name.clone(), MenuAction::Level(index)))
             .collect();
 
+        buttons.push(("Daily challenge".to_string(), MenuAction::Daily));
+        buttons.push(("Daily calendar".to_string(), MenuAction::DailyCalendar));
+        (2..=4).for_each(|players| {
+            buttons.push((
+                format!("Hot-seat, {} players", players),
+                MenuAction::HotSeat(players),
+            ))
+        });
+        buttons.push(("Online race".to_string(), MenuAction::Online));
+        config
+            .puzzle_packs
+            .iter()
+            .enumerate()
+            .for_each(|(index, pack)| {
+                buttons.push((
+                    format!("Puzzles: {}", pack.name),
+                    MenuAction::PuzzlePack(index),
+                ))
+            });
+        Pattern::TRAINED.iter().for_each(|pattern| {
+            buttons.push((
+                format!("Training: {}", pattern.name()),
+                MenuAction::Training(*pattern),
+            ))
+        });
+        buttons.push(("Settings".to_string(), MenuAction::Settings));
+
         GameControls { buttons }
     }
 
     pub fn draw(&self) {
-        let full_height = HEIGHT + PADDING;
-        let (top_margin, left_margin) = self.calculate_margins();
-
+        let (scale, _) = self.layout();
         let mut ui = root_ui();
-        self.buttons.iter().enumerate().for_each(|(i, button)| {
-            let y = top_margin + (i as f32) * full_height;
-            widgets::Button::new(button.clone())
-                .size(vec2(WIDTH, HEIGHT))
-                .position(vec2(left_margin, y))
-                .ui(&mut ui);
-        });
-    }
 
-    pub fn handle_input(&self, pos: Vector2<f32>) -> Option<(ButtonIndex, ButtonLabel)> {
-        let full_height = HEIGHT + PADDING;
-        let (top_margin, left_margin) = self.calculate_margins();
+        let skin = scaled_skin(&ui, scale);
+        ui.push_skin(&skin);
+        self.buttons
+            .iter()
+            .enumerate()
+            .for_each(|(i, (button, _))| {
+                let pos = self.button_position(i);
+                widgets::Button::new(button.clone())
+                    .size(vec2(WIDTH * scale, HEIGHT * scale))
+                    .position(vec2(pos.x, pos.y))
+                    .ui(&mut ui);
+            });
+        ui.pop_skin();
+    }
 
+    pub fn handle_input(&self, pos: Vector2<f32>) -> Option<MenuAction> {
         self.buttons
             .iter()
             .enumerate()
             .find(|(i, _)| {
-                let x = left_margin;
-                let y = top_margin + (*i as f32) * full_height;
-
-                GameControls::intersects(x, y, pos)
+                let button_pos = self.button_position(*i);
+                self.intersects(button_pos.x, button_pos.y, pos)
             })
-            .map(|(index, button)| (index, button.clone()))
+            .map(|(_, (_, action))| *action)
+    }
+
+    /// Buttons are stacked in columns, as many as needed to fit the screen height.
+    fn button_position(&self, index: usize) -> Vector2<f32> {
+        let (scale, rows) = self.layout();
+        let full_height = (HEIGHT + PADDING) * scale;
+        let full_width = (WIDTH + PADDING) * scale;
+        let cols = self.buttons.len().div_ceil(rows);
+
+        let top_margin = (screen_height() - rows as f32 * full_height) / 2.0;
+        let left_margin = (screen_width() - cols as f32 * full_width) / 2.0;
+
+        Vector2::new(
+            left_margin + (index / rows) as f32 * full_width,
+            top_margin + (index % rows) as f32 * full_height,
+        )
     }
 
-    fn calculate_margins(&self) -> (f32, f32) {
-        let full_height = HEIGHT + PADDING;
-        let all_buttons_height = (self.buttons.len() as f32) * full_height;
-        let top_margin = (screen_height() - all_buttons_height) / 2.0;
-        let left_margin = (screen_width() - WIDTH) / 2.0;
-        (top_margin, left_margin)
+    fn intersects(&self, x: f32, y: f32, pos: Vector2<f32>) -> bool {
+        let (scale, _) = self.layout();
+        pos.x >= x && pos.x <= x + WIDTH * scale && pos.y >= y && pos.y <= y + HEIGHT * scale
     }
 
-    fn intersects(x: f32, y: f32, pos: Vector2<f32>) -> bool {
-        pos.x >= x && pos.x <= x + WIDTH && pos.y >= y && pos.y <= y + HEIGHT
+    /// Scale of the buttons and rows of them in a column. Buttons follow the
+    /// UI scale, but shrink when their columns would be wider than the window.
+    fn layout(&self) -> (f32, usize) {
+        let rows = |scale: f32| {
+            let rows = ((screen_height() - PADDING * scale * 2.0) / ((HEIGHT + PADDING) * scale))
+                .max(1.0) as usize;
+            rows.min(self.buttons.len()).max(1)
+        };
+
+        let scale = ui_scale();
+        let cols = self.buttons.len().div_ceil(rows(scale));
+        let width = cols as f32 * (WIDTH + PADDING) * scale;
+        let scale = match width > screen_width() {
+            true => scale * screen_width() / width,
+            false => scale,
+        };
+
+        (scale, rows(scale))
+    }
+}
+
+/// The default skin with the font of buttons, labels and checkboxes scaled.
+pub fn scaled_skin(ui: &Ui, scale: f32) -> Skin {
+    let font_size = (FONT_SIZE * scale) as u16;
+    let label_style = ui
+        .style_builder()
+        .margin(RectOffset::new(2.0, 2.0, 2.0, 2.0))
+        .text_color(Color::from_rgba(0, 0, 0, 255))
+        .color_inactive(Color::from_rgba(0, 0, 0, 128))
+        .font_size(font_size)
+        .build();
+    let button_style = ui
+        .style_builder()
+        .margin(RectOffset::new(2.0, 2.0, 2.0, 2.0))
+        .color(Color::from_rgba(204, 204, 204, 235))
+        .color_clicked(Color::from_rgba(187, 187, 187, 255))
+        .color_hovered(Color::from_rgba(170, 170, 170, 235))
+        .text_color(Color::from_rgba(0, 0, 0, 255))
+        .font_size(font_size)
+        .build();
+    let checkbox_style = ui
+        .style_builder()
+        .text_color(Color::from_rgba(0, 0, 0, 255))
+        .color(Color::from_rgba(200, 200, 200, 255))
+        .color_hovered(Color::from_rgba(210, 210, 210, 255))
+        .color_clicked(Color::from_rgba(150, 150, 150, 255))
+        .color_selected(Color::from_rgba(128, 128, 128, 255))
+        .color_selected_hovered(Color::from_rgba(140, 140, 140, 255))
+        .font_size(font_size)
+        .build();
+
+    Skin {
+        label_style,
+        button_style,
+        checkbox_style,
+        ..ui.default_skin()
     }
 }
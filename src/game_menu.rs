@@ -0,0 +1,129 @@
+use macroquad::{
+    hash,
+    math::vec2,
+    ui::{root_ui, widgets},
+    window::{screen_height, screen_width},
+};
+
+use crate::{
+    game::Game,
+    game_controls::{scaled_skin, HEIGHT, WIDTH},
+    grid::ui_scale,
+};
+
+/// What can be done with a game in play.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameCommand {
+    Restart,
+    NewBoard,
+    QuitToMenu,
+}
+
+impl GameCommand {
+    fn name(&self) -> &'static str {
+        match self {
+            GameCommand::Restart => "Restart this board",
+            GameCommand::NewBoard => "New board",
+            GameCommand::QuitToMenu => "Quit to menu",
+        }
+    }
+}
+
+/// A window over the game to restart it or leave it, asking first when that
+/// loses the moves made.
+#[derive(Debug, Default)]
+pub struct GameMenu {
+    open: bool,
+    /// command waiting for the player to confirm it
+    confirming: Option<GameCommand>,
+}
+
+impl GameMenu {
+    pub fn new() -> Self {
+        GameMenu {
+            open: false,
+            confirming: None,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.confirming = None;
+    }
+
+    /// The command to run now, or none while it waits to be confirmed.
+    pub fn request(&mut self, command: GameCommand, game: &Game) -> Option<GameCommand> {
+        if !game.has_progress() {
+            self.open = false;
+            return Some(command);
+        }
+
+        self.open = true;
+        self.confirming = Some(command);
+        None
+    }
+
+    /// Draws the menu when it is open, with the command chosen and confirmed.
+    pub fn draw(&mut self, game: &Game) -> Option<GameCommand> {
+        if !self.open {
+            return None;
+        }
+
+        let scale = ui_scale();
+        let size = vec2(WIDTH * 2.0 * scale, HEIGHT * 6.0 * scale);
+        let pos = vec2(
+            (screen_width() - size.x) / 2.0,
+            (screen_height() - size.y) / 2.0,
+        );
+
+        let mut chosen = None;
+        let mut ui = root_ui();
+        let skin = scaled_skin(&ui, scale);
+        ui.push_skin(&skin);
+        widgets::Window::new(hash!(), pos, size)
+            .label("Game")
+            .movable(false)
+            .ui(&mut ui, |ui| match self.confirming {
+                Some(command) => {
+                    ui.label(None, &format!("{}?", command.name()));
+                    ui.label(None, "The game so far counts as lost.");
+                    if ui.button(None, "Yes") {
+                        chosen = Some(command);
+                        self.open = false;
+                    }
+                    if ui.button(None, "No") {
+                        self.open = false;
+                    }
+                }
+                None => {
+                    if ui.button(None, "Resume") {
+                        self.open = false;
+                    }
+                    let mut commands = vec![GameCommand::QuitToMenu];
+                    if game.can_start_again() {
+                        commands.insert(0, GameCommand::Restart);
+                        commands.insert(1, GameCommand::NewBoard);
+                    }
+                    for command in commands {
+                        if ui.button(None, command.name()) {
+                            chosen = Some(command);
+                        }
+                    }
+                }
+            });
+        ui.pop_skin();
+
+        match (chosen, self.confirming) {
+            (Some(command), None) => self.request(command, game),
+            (chosen, _) if !self.open => {
+                self.confirming = None;
+                chosen
+            }
+            _ => None,
+        }
+    }
+}
@@ -1,16 +1,36 @@
+use std::path::Path;
+
 use macroquad::texture::{load_texture, Texture2D};
 
-#[derive(Debug, PartialEq)]
+/// Textures of the tiles. Without a texture the tile is drawn with the
+/// colours of the theme.
+#[derive(Debug, Clone, PartialEq)]
 pub struct GameTextures {
-    pub bomb: Texture2D,
-    pub flag: Texture2D,
+    pub hidden: Option<Texture2D>,
+    pub revealed: Option<Texture2D>,
+    pub flag: Option<Texture2D>,
+    pub mine: Option<Texture2D>,
+    pub wrong_flag: Option<Texture2D>,
 }
 
 impl GameTextures {
     pub async fn new() -> GameTextures {
-        let bomb: Texture2D = load_texture("textures/bomb.png").await.unwrap();
-        let flag: Texture2D = load_texture("textures/flag.png").await.unwrap();
+        GameTextures {
+            hidden: None,
+            revealed: None,
+            flag: GameTextures::load(Path::new("textures/flag.png")).await,
+            mine: GameTextures::load(Path::new("textures/bomb.png")).await,
+            wrong_flag: None,
+        }
+    }
 
-        GameTextures { bomb, flag }
+    pub async fn load(path: &Path) -> Option<Texture2D> {
+        match load_texture(&path.to_string_lossy()).await {
+            Ok(texture) => Some(texture),
+            Err(err) => {
+                eprintln!("Could not load the texture {}: {}", path.display(), err);
+                None
+            }
+        }
     }
 }
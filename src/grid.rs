@@ -1,13 +1,46 @@
-use macroquad::window::{screen_height, screen_width};
+use macroquad::window::{screen_dpi_scale, screen_height, screen_width};
 
 use crate::vector2::Vector2;
 
-const GRID_COLUMNS: f32 = 12.0;
+/// Sizes in pixels throughout the UI are for a window of this size at a DPI
+/// scale of 1, and are multiplied by [`ui_scale`].
+const REFERENCE_SIZE: Vector2<f32> = Vector2::new(800.0, 600.0);
+const MIN_SCALE: f32 = 0.75;
+const MAX_SCALE: f32 = 4.0;
+
+/// Height of the header and of the footer before scaling.
+const BAR_HEIGHT: f32 = 50.0;
+
+/// How much larger than designed the UI is drawn, following the window but
+/// never smaller than the DPI scale of the screen.
+pub fn ui_scale() -> f32 {
+    let window = (screen_width() / REFERENCE_SIZE.x).min(screen_height() / REFERENCE_SIZE.y);
+
+    window.max(screen_dpi_scale()).clamp(MIN_SCALE, MAX_SCALE)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Section {
+    Header,
+    Body,
+    Footer,
+}
+
+/// Parts of a bar, so what is written in it doesn't overlap: text on either
+/// side and the minimap in the middle quarter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Slot {
+    Left,
+    Middle,
+    Right,
+}
+
+/// Space kept between the slots before scaling.
+const SLOT_GAP: f32 = 10.0;
 
 #[derive(Debug)]
 pub struct GridSection {
-    pub start: f32,
-    pub cols: f32,
+    pub section: Section,
     pub padding_top: f32,
     pub padding_left: f32,
 }
@@ -21,21 +54,53 @@ pub struct Grid {
 
 impl GridSection {
     pub fn pos(&self) -> Vector2<f32> {
-        let x = self.padding_left;
-
-        let h = screen_height();
-        let y = self.padding_top + (self.start / GRID_COLUMNS) * h;
+        let scale = ui_scale();
+        let (top, _) = self.bounds();
 
-        Vector2::new(x, y)
+        Vector2::new(self.padding_left * scale, top + self.padding_top * scale)
     }
 
     pub fn screen_size(&self) -> Vector2<f32> {
-        let x = screen_width() - self.padding_left * 2.0;
+        let scale = ui_scale();
+        let (top, bottom) = self.bounds();
 
+        let x = screen_width() - self.padding_left * scale * 2.0;
+        let y = (bottom - top) - self.padding_top * scale * 2.0;
+
+        Vector2::new(x, y.max(0.0))
+    }
+
+    /// Left edge and width of a slot of the section.
+    pub fn slot(&self, slot: Slot) -> (f32, f32) {
+        let x = self.pos().x;
+        let width = self.screen_size().x;
+        let gap = SLOT_GAP * ui_scale();
+        let side = (width * 3.0 / 8.0 - gap).max(0.0);
+
+        match slot {
+            Slot::Left => (x, side),
+            Slot::Middle => (x + width * 3.0 / 8.0, width / 4.0),
+            Slot::Right => (x + width - side, side),
+        }
+    }
+
+    /// Top and bottom of the section, with the header and footer keeping their
+    /// scaled height and the body taking the rest of the window.
+    fn bounds(&self) -> (f32, f32) {
         let h = screen_height();
-        let y = (self.cols / GRID_COLUMNS * h) - (self.padding_top * 2.0);
+        let bar = (BAR_HEIGHT * ui_scale()).min(h / 4.0);
+
+        match self.section {
+            Section::Header => (0.0, bar),
+            Section::Body => (bar, h - bar),
+            Section::Footer => (h - bar, h),
+        }
+    }
+}
 
-        Vector2::new(x, y)
+impl Default for Grid {
+    fn default() -> Self {
+        Grid::new()
     }
 }
 
@@ -43,20 +108,17 @@ impl Grid {
     pub fn new() -> Self {
         Grid {
             header: GridSection {
-                start: 0.0,
-                cols: 1.0,
+                section: Section::Header,
                 padding_top: 5.0,
                 padding_left: 20.0,
             },
             body: GridSection {
-                start: 1.0,
-                cols: 10.0,
+                section: Section::Body,
                 padding_top: 0.0,
                 padding_left: 20.0,
             },
             footer: GridSection {
-                start: 11.0,
-                cols: 1.0,
+                section: Section::Footer,
                 padding_top: 5.0,
                 padding_left: 20.0,
             },
@@ -0,0 +1,138 @@
+//! Hints name the pattern behind the next certain move, so players learn why
+//! a tile is safe or a mine rather than just where.
+
+use crate::{
+    bot::{BotAction, Deduction, Solver, Technique, VisibleBoard, VisibleTile},
+    vector3::Vector3,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+    /// one number has as many hidden tiles as mines left, or none
+    Single,
+    OneOne,
+    OneTwo,
+    OneTwoOne,
+    OneTwoTwoOne,
+    /// any other pair of numbers where one's tiles are all around the other
+    Subset,
+    MineCount,
+}
+
+impl Pattern {
+    /// The patterns the training mode poses.
+    pub const TRAINED: [Pattern; 6] = [
+        Pattern::OneOne,
+        Pattern::OneTwo,
+        Pattern::OneTwoOne,
+        Pattern::OneTwoTwoOne,
+        Pattern::Subset,
+        Pattern::MineCount,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Pattern::Single => "single number",
+            Pattern::OneOne => "1-1",
+            Pattern::OneTwo => "1-2",
+            Pattern::OneTwoOne => "1-2-1",
+            Pattern::OneTwoTwoOne => "1-2-2-1",
+            Pattern::Subset => "subset reduction",
+            Pattern::MineCount => "global mine count",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    pub pattern: Pattern,
+    pub action: BotAction,
+    /// the numbers the move follows from
+    pub reasons: Vec<Vector3<i32>>,
+}
+
+impl Hint {
+    pub fn find(board: &VisibleBoard) -> Option<Self> {
+        let deduction = Solver::deduce(board)?;
+
+        Some(Hint {
+            pattern: pattern(board, &deduction),
+            action: deduction.action,
+            reasons: deduction.reasons,
+        })
+    }
+
+    pub fn target(&self) -> Vector3<i32> {
+        match self.action {
+            BotAction::Reveal(pos) | BotAction::Guess(pos) | BotAction::Flag(pos) => pos,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let verdict = match self.action {
+            BotAction::Flag(_) => "a mine",
+            _ => "safe",
+        };
+
+        format!(
+            "Hint ({}): the outlined tile is {}",
+            self.pattern.name(),
+            verdict
+        )
+    }
+}
+
+/// Mines a revealed number still misses, not counting its flagged neighbours.
+fn mines_left(board: &VisibleBoard, pos: Vector3<i32>) -> Option<i32> {
+    if !board.within_bounds(pos) {
+        return None;
+    }
+
+    let VisibleTile::Number(number) = board.get(pos) else {
+        return None;
+    };
+
+    let known = board
+        .neighbors(pos)
+        .filter(|other| matches!(board.get(*other), VisibleTile::Flagged | VisibleTile::Mine))
+        .count() as i32;
+
+    Some(number - known)
+}
+
+/// Names a pair of numbers next to each other in a row or column after the
+/// line of numbers they are part of.
+fn pattern(board: &VisibleBoard, deduction: &Deduction) -> Pattern {
+    match (deduction.technique, deduction.reasons.as_slice()) {
+        (Technique::Single, _) => Pattern::Single,
+        (Technique::MineCount, _) => Pattern::MineCount,
+        (Technique::Subset, [smaller, larger]) => {
+            let direction = Vector3::new(
+                larger.x - smaller.x,
+                larger.y - smaller.y,
+                larger.z - smaller.z,
+            );
+            if direction.z != 0 || direction.x.abs() + direction.y.abs() != 1 {
+                return Pattern::Subset;
+            }
+
+            let along = |steps: i32| {
+                let pos = Vector3::new(
+                    larger.x + direction.x * steps,
+                    larger.y + direction.y * steps,
+                    larger.z,
+                );
+                mines_left(board, pos)
+            };
+
+            match (along(-1), along(0), along(1), along(2)) {
+                (Some(1), Some(2), Some(2), Some(1)) => Pattern::OneTwoTwoOne,
+                (Some(1), Some(2), Some(1), _) => Pattern::OneTwoOne,
+                (Some(1), Some(2), _, _) => Pattern::OneTwo,
+                (Some(1), Some(1), _, _) => Pattern::OneOne,
+                _ => Pattern::Subset,
+            }
+        }
+        (Technique::Subset, _) => Pattern::Subset,
+    }
+}
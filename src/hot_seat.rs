@@ -0,0 +1,67 @@
+/// Points for every tile a player reveals.
+const REVEAL_POINTS: i32 = 1;
+/// Points lost when a player reveals a mine.
+const MINE_PENALTY: i32 = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Player {
+    pub name: String,
+    pub score: i32,
+    pub mines_hit: i32,
+}
+
+/// Players taking turns on the same board. Revealing tiles scores points and
+/// hitting a mine costs a penalty instead of ending the game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HotSeat {
+    pub players: Vec<Player>,
+    pub current: usize,
+}
+
+impl HotSeat {
+    pub fn new(players_count: usize) -> Self {
+        let players = (1..=players_count)
+            .map(|i| Player {
+                name: format!("Player {}", i),
+                score: 0,
+                mines_hit: 0,
+            })
+            .collect();
+
+        HotSeat {
+            players,
+            current: 0,
+        }
+    }
+
+    pub fn current_player(&self) -> &Player {
+        &self.players[self.current]
+    }
+
+    /// Scores the move of the current player and passes the turn. Moves that
+    /// change nothing on the board keep the turn.
+    pub fn end_turn(&mut self, revealed_tiles: i32, hit_mine: bool) {
+        if revealed_tiles == 0 && !hit_mine {
+            return;
+        }
+
+        let player = &mut self.players[self.current];
+        player.score += revealed_tiles * REVEAL_POINTS;
+        if hit_mine {
+            player.score -= MINE_PENALTY;
+            player.mines_hit += 1;
+        }
+
+        self.current = (self.current + 1) % self.players.len();
+    }
+
+    /// The players with the highest score, more than one on a draw.
+    pub fn leaders(&self) -> Vec<&Player> {
+        let best = self.players.iter().map(|player| player.score).max();
+
+        self.players
+            .iter()
+            .filter(|player| Some(player.score) == best)
+            .collect()
+    }
+}
@@ -0,0 +1,242 @@
+//! The header of a game in the style of the original: the mines left and the
+//! time on seven-segment counters either side of a face that restarts the
+//! current board, with a bar of the revealed tiles along the bottom.
+
+use macroquad::{
+    color::{Color, BLACK, DARKGRAY, GRAY, LIGHTGRAY, WHITE, YELLOW},
+    shapes::{draw_circle, draw_circle_lines, draw_line, draw_rectangle},
+    text::{draw_text, measure_text},
+};
+
+use crate::{
+    grid::{ui_scale, GridSection},
+    messages::font_size,
+    vector2::Vector2,
+};
+
+const LIT: Color = Color::from_hex(0xff0000);
+const UNLIT: Color = Color::from_hex(0x400000);
+const PROGRESS: Color = Color::from_hex(0x3a9a3a);
+
+/// Width of a digit and of the gap after it, by the height of the counter.
+const DIGIT_WIDTH: f32 = 0.5;
+const DIGIT_GAP: f32 = 0.1;
+/// Height of the progress bar, by the height of the header.
+const PROGRESS_HEIGHT: f32 = 0.08;
+
+/// Segments lit for each digit, from `a` at the top round to `f` and then `g`
+/// in the middle.
+const SEGMENTS: [u8; 10] = [
+    0b0111111, 0b0000110, 0b1011011, 0b1001111, 0b1100110, 0b1101101, 0b1111101, 0b0000111,
+    0b1111111, 0b1101111,
+];
+const MINUS: u8 = 0b1000000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Face {
+    Smiling,
+    /// while a tile is being pressed
+    Surprised,
+    Won,
+    Lost,
+}
+
+/// Where the face is drawn: a square the height of the section, centred.
+pub fn face_bounds(grid: &GridSection) -> (Vector2<f32>, f32) {
+    let pos = grid.pos();
+    let size = grid.screen_size();
+
+    (Vector2::new(pos.x + (size.x - size.y) / 2.0, pos.y), size.y)
+}
+
+pub fn is_on_face(pos: Vector2<f32>, grid: &GridSection) -> bool {
+    let (face_pos, size) = face_bounds(grid);
+    let diff = pos.sub(face_pos);
+
+    diff.x >= 0.0 && diff.y >= 0.0 && diff.x <= size && diff.y <= size
+}
+
+/// Draws the face on a raised button, sunk while `pressed`.
+pub fn draw_face(face: Face, pressed: bool, grid: &GridSection) {
+    let (pos, size) = face_bounds(grid);
+    let bevel = (size / 12.0).max(1.0);
+    let (light, dark) = match pressed {
+        true => (GRAY, WHITE),
+        false => (WHITE, GRAY),
+    };
+
+    draw_rectangle(pos.x, pos.y, size, size, dark);
+    draw_rectangle(pos.x, pos.y, size - bevel, size - bevel, light);
+    draw_rectangle(
+        pos.x + bevel,
+        pos.y + bevel,
+        size - bevel * 2.0,
+        size - bevel * 2.0,
+        LIGHTGRAY,
+    );
+
+    let center = pos.add_val(size / 2.0);
+    let radius = size * 0.32;
+    let line = (size / 20.0).max(1.0);
+    draw_circle(center.x, center.y, radius, YELLOW);
+    draw_circle_lines(center.x, center.y, radius, line, BLACK);
+
+    let eye = radius * 0.35;
+    let eye_y = center.y - radius * 0.3;
+    for side in [-1.0, 1.0] {
+        let x = center.x + side * eye;
+        match face {
+            Face::Lost => {
+                let half = radius * 0.12;
+                draw_line(x - half, eye_y - half, x + half, eye_y + half, line, BLACK);
+                draw_line(x - half, eye_y + half, x + half, eye_y - half, line, BLACK);
+            }
+            Face::Won => draw_rectangle(
+                x - radius * 0.22,
+                eye_y - radius * 0.12,
+                radius * 0.44,
+                radius * 0.24,
+                BLACK,
+            ),
+            _ => draw_circle(x, eye_y, radius * 0.1, BLACK),
+        }
+    }
+    if face == Face::Won {
+        draw_line(center.x - eye, eye_y, center.x + eye, eye_y, line, BLACK);
+    }
+
+    let mouth_y = center.y + radius * 0.4;
+    let mouth = radius * 0.45;
+    // a smile, or a frown when lost
+    let curve = match face {
+        Face::Lost => -radius * 0.1,
+        _ => radius * 0.1,
+    };
+    match face {
+        Face::Surprised => draw_circle_lines(center.x, mouth_y, radius * 0.15, line, BLACK),
+        _ => {
+            let (left, right) = (center.x - mouth, center.x + mouth);
+            draw_line(
+                left,
+                mouth_y - curve,
+                center.x,
+                mouth_y + curve,
+                line,
+                BLACK,
+            );
+            draw_line(
+                center.x,
+                mouth_y + curve,
+                right,
+                mouth_y - curve,
+                line,
+                BLACK,
+            );
+        }
+    }
+}
+
+pub fn counter_width(text: &str, height: f32) -> f32 {
+    let width = text
+        .chars()
+        .map(|c| match c {
+            ':' => DIGIT_WIDTH / 2.0,
+            _ => DIGIT_WIDTH,
+        })
+        .sum::<f32>()
+        + DIGIT_GAP * (text.chars().count() + 1) as f32;
+
+    width * height
+}
+
+/// Draws digits, `-` and `:` on a black panel with its top left at `pos`.
+pub fn draw_counter(text: &str, pos: Vector2<f32>, height: f32) {
+    draw_rectangle(pos.x, pos.y, counter_width(text, height), height, BLACK);
+
+    let gap = DIGIT_GAP * height;
+    let mut x = pos.x + gap;
+    for c in text.chars() {
+        match c {
+            ':' => {
+                let dot = height / 10.0;
+                let center = x + DIGIT_WIDTH * height / 4.0;
+                for y in [height / 3.0, height * 2.0 / 3.0] {
+                    draw_rectangle(center - dot / 2.0, pos.y + y - dot / 2.0, dot, dot, LIT);
+                }
+                x += (DIGIT_WIDTH / 2.0) * height + gap;
+            }
+            _ => {
+                let segments = match c {
+                    '-' => MINUS,
+                    _ => c.to_digit(10).map_or(0, |digit| SEGMENTS[digit as usize]),
+                };
+                draw_digit(
+                    segments,
+                    Vector2::new(x, pos.y + gap),
+                    DIGIT_WIDTH * height,
+                    height - gap * 2.0,
+                );
+                x += DIGIT_WIDTH * height + gap;
+            }
+        }
+    }
+}
+
+fn draw_digit(segments: u8, pos: Vector2<f32>, width: f32, height: f32) {
+    let thickness = width / 5.0;
+    let half = (height - thickness) / 2.0;
+    let (left, right) = (pos.x, pos.x + width - thickness);
+    let (top, middle, bottom) = (pos.y, pos.y + half, pos.y + half * 2.0);
+
+    // x, y, width and height of segments a to g
+    let rects = [
+        (left, top, width, thickness),
+        (right, top, thickness, half + thickness),
+        (right, middle, thickness, half + thickness),
+        (left, bottom, width, thickness),
+        (left, middle, thickness, half + thickness),
+        (left, top, thickness, half + thickness),
+        (left, middle, width, thickness),
+    ];
+
+    // unlit segments first, so the lit ones are drawn over where they cross
+    for lit in [false, true] {
+        for (i, (x, y, w, h)) in rects.iter().enumerate() {
+            if (segments >> i & 1 == 1) == lit {
+                let color = match lit {
+                    true => LIT,
+                    false => UNLIT,
+                };
+                draw_rectangle(*x, *y, *w, *h, color);
+            }
+        }
+    }
+}
+
+/// Fills a bar along the bottom of the section by `progress` from 0 to 1.
+pub fn draw_progress(progress: f32, grid: &GridSection) {
+    let pos = grid.pos();
+    let size = grid.screen_size();
+    let height = (size.y * PROGRESS_HEIGHT).max(2.0);
+    let y = pos.y + size.y + (grid.padding_top * ui_scale() - height) / 2.0;
+
+    draw_rectangle(pos.x, y, size.x, height, DARKGRAY);
+    draw_rectangle(
+        pos.x,
+        y,
+        size.x * progress.clamp(0.0, 1.0),
+        height,
+        PROGRESS,
+    );
+}
+
+/// Writes the 3BV per second ending at `right`.
+pub fn write_three_bv_per_second(value: f32, right: f32, color: Color, grid: &GridSection) {
+    let text = format!("{:.2} 3BV/s", value);
+    let font_size = font_size() * 0.8;
+    let dimensions = measure_text(&text, None, font_size as u16, 1.0);
+    let pos = grid.pos();
+    let y = pos.y + (grid.screen_size().y - dimensions.height) / 2.0 + dimensions.offset_y;
+
+    draw_text(&text, right - dimensions.width, y, font_size, color);
+}
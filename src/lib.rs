@@ -0,0 +1,36 @@
+pub mod animation;
+pub mod bindings;
+pub mod board_cache;
+pub mod board_text;
+pub mod bot;
+pub mod camera;
+pub mod config;
+pub mod daily;
+pub mod diagnostics;
+pub mod difficulty;
+pub mod events;
+pub mod game;
+pub mod game_controls;
+pub mod game_menu;
+pub mod game_textures;
+pub mod grid;
+pub mod hint;
+pub mod hot_seat;
+pub mod hud;
+pub mod mbf;
+pub mod messages;
+pub mod mouse;
+pub mod online;
+pub mod palette;
+pub mod puzzle;
+pub mod replay;
+pub mod rules;
+pub mod settings;
+pub mod settings_screen;
+pub mod sound;
+pub mod stats;
+pub mod theme;
+pub mod tile;
+pub mod utils;
+pub mod vector2;
+pub mod vector3;
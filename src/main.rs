@@ -1,57 +1,321 @@
-mod config;
-mod diagnostics;
-mod game;
-mod game_controls;
-mod game_textures;
-mod grid;
-mod messages;
-mod mouse;
-mod tile;
-mod utils;
-mod vector2;
-
-use config::Config;
-use diagnostics::Diagnostics;
-use game::{Game, GameState};
-use macroquad::prelude::{clear_background, next_frame, WHITE};
-use mouse::{is_mouse_left_btn_pressed, is_mouse_right_btn_pressed};
-
-#[macroquad::main("Minesweeper")]
+use std::env;
+
+use macroquad::prelude::{clear_background, is_key_pressed, next_frame, Conf, KeyCode};
+use minesweeper::{
+    bindings::Command,
+    config::Config,
+    daily::DailyCalendar,
+    diagnostics::{Diagnostics, Phase},
+    events::EventStream,
+    game::{Game, GameState},
+    game_controls::{self, MenuAction},
+    game_menu::{GameCommand, GameMenu},
+    grid,
+    messages::{write_opponents_progress, write_training_search, write_waiting_for_opponents},
+    mouse::{cursor_position, is_mouse_left_btn_pressed, is_mouse_right_btn_pressed},
+    online::{ClientStatus, OnlineClient, DEFAULT_SERVER},
+    puzzle::{PuzzlePack, PuzzlePicker},
+    replay::{load_replay, ReplayPlayer},
+    settings::Settings,
+    settings_screen::SettingsScreen,
+    theme::Theme,
+    utils::{current_date, parse_args},
+};
+
+fn window_conf() -> Conf {
+    Conf {
+        window_title: "Minesweeper".to_string(),
+        high_dpi: true,
+        window_resizable: true,
+        ..Default::default()
+    }
+}
+
+#[macroquad::main(window_conf)]
 async fn main() {
-    let mut game = Game::new(grid::Grid::new()).await;
+    let mut settings = Settings::load();
+    // kept for what the other themes leave out
+    let default_theme = Theme::default_theme().await;
+    let mut game = Game::new(grid::Grid::new(), &settings, &default_theme).await;
 
     let mut diagnostics = Diagnostics::new();
+    diagnostics.set_visible(settings.diagnostics);
 
     let config = Config::new();
     let controls = game_controls::GameControls::new(&config);
 
+    let (options, files) = parse_args(env::args().skip(1));
+    let server = options
+        .get("server")
+        .cloned()
+        .unwrap_or(DEFAULT_SERVER.to_string());
+    let player_name = options
+        .get("name")
+        .cloned()
+        .or(env::var("USER").ok())
+        .unwrap_or("Player".to_string());
+
+    if let Some(target) = options.get("events") {
+        match EventStream::open(target) {
+            Ok(stream) => game.set_event_stream(stream),
+            Err(err) => eprintln!("Could not open the event stream {}: {}", target, err),
+        }
+    }
+
+    // a replay file passed on the command line is played back on start
+    let mut replay_player = files.first().and_then(|path| match load_replay(path) {
+        Ok(replay) => Some(ReplayPlayer::new(&replay, &mut game)),
+        Err(err) => {
+            eprintln!("Could not load the replay {}: {}", path, err);
+            None
+        }
+    });
+
+    let mut calendar: Option<DailyCalendar> = None;
+    let mut puzzle_picker: Option<PuzzlePicker> = None;
+    let mut online: Option<OnlineClient> = None;
+    let mut settings_screen: Option<SettingsScreen> = None;
+    let mut game_menu = GameMenu::new();
+
     loop {
-        clear_background(WHITE);
+        diagnostics.start_frame();
+        clear_background(game.background_color());
+
+        if let Some(daily_calendar) = &mut calendar {
+            daily_calendar.draw(&game.colors());
+            if is_key_pressed(KeyCode::Left) {
+                daily_calendar.previous_month();
+            } else if is_key_pressed(KeyCode::Right) {
+                daily_calendar.next_month();
+            } else if is_mouse_left_btn_pressed().is_some() || is_key_pressed(KeyCode::Escape) {
+                calendar = None;
+            }
+
+            diagnostics.on_loop(&game);
+            next_frame().await;
+            continue;
+        }
+
+        if let Some(screen) = &mut settings_screen {
+            let previous = settings.clone();
+            let closed = screen.draw(&mut settings);
+            if settings.theme != previous.theme || settings.palette != previous.palette {
+                game.set_theme(Theme::from_settings(&settings, &default_theme).await);
+            }
+            if settings != previous {
+                game.apply_settings(&settings);
+                diagnostics.set_visible(settings.diagnostics);
+            }
+            if closed {
+                settings.save();
+                settings_screen = None;
+            }
+
+            diagnostics.on_loop(&game);
+            next_frame().await;
+            continue;
+        }
+
+        if let Some(picker) = &puzzle_picker {
+            picker.draw(&game.colors());
+            if let Some(pos) = is_mouse_left_btn_pressed() {
+                if let Some(path) = picker.handle_input(pos) {
+                    replay_player = None;
+                    game.start_puzzle(&path);
+                    puzzle_picker = None;
+                }
+            } else if is_key_pressed(KeyCode::Escape) {
+                puzzle_picker = None;
+            }
+
+            diagnostics.on_loop(&game);
+            next_frame().await;
+            continue;
+        }
+
+        if let Some(pattern) = game.training_search() {
+            game.continue_training_search();
+            write_training_search(pattern, game.colors().text);
+            if is_key_pressed(KeyCode::Escape) {
+                game.cancel_training_search();
+            }
+            diagnostics.measure(Phase::Update);
+
+            diagnostics.on_loop(&game);
+            next_frame().await;
+            continue;
+        }
+
+        if let Some(client) = &mut online {
+            if let Some(start) = client.poll() {
+                replay_player = None;
+                game.start(start.rows, start.cols, 1, start.mines, start.seed);
+            }
+            client.report(&game);
+            diagnostics.measure(Phase::Update);
+
+            let waiting = matches!(
+                client.status,
+                ClientStatus::Connecting | ClientStatus::Waiting
+            );
+            if waiting || game.get_state() == GameState::NotStarted {
+                write_waiting_for_opponents(client, game.colors().text);
+                if is_key_pressed(KeyCode::Escape) {
+                    online = None;
+                }
+
+                diagnostics.on_loop(&game);
+                next_frame().await;
+                continue;
+            }
+        }
+
+        game.update_camera(cursor_position(), is_mouse_left_btn_pressed().is_some());
+        diagnostics.measure(Phase::Input);
+
+        // any click or Space skips the animations, clicks still go to the game
+        game.update_animations(
+            is_mouse_left_btn_pressed().is_some()
+                || is_mouse_right_btn_pressed().is_some()
+                || settings.bindings.is_pressed(Command::SkipAnimations),
+        );
+        diagnostics.measure(Phase::Update);
+
+        // the face in the header restarts the board, unless under a button
+        // of the menu
+        let mut command: Option<GameCommand> = None;
+        if let Some(pos) = is_mouse_left_btn_pressed() {
+            let in_menu =
+                game.get_state() != GameState::Playing && controls.handle_input(pos).is_some();
+            if !in_menu && !game_menu.is_open() && game.can_start_again() && game.is_on_face(pos) {
+                command = game_menu.request(GameCommand::Restart, &game);
+            }
+        }
 
         match game.get_state() {
             GameState::NotStarted | GameState::GameOver | GameState::GameWon => {
                 controls.draw();
-                if let Some(pos) = is_mouse_left_btn_pressed() {
-                    match controls.handle_input(pos) {
-                        Some((index, _level)) => {
-                            let level = &config.levels[index];
-                            game.start(level.rows, level.cols, level.mines);
+                if let Some(action) =
+                    is_mouse_left_btn_pressed().and_then(|pos| controls.handle_input(pos))
+                {
+                    replay_player = None;
+                    online = None;
+                    match action {
+                        MenuAction::Level(index) => game.start_level(&config.levels[index]),
+                        MenuAction::Daily => game.start_daily(&config.daily, current_date()),
+                        MenuAction::HotSeat(players) => {
+                            game.start_hot_seat(&config.hot_seat, players)
+                        }
+                        MenuAction::DailyCalendar => {
+                            calendar = Some(DailyCalendar::new(current_date()))
+                        }
+                        MenuAction::Training(pattern) => game.start_training(pattern),
+                        MenuAction::Settings => settings_screen = Some(SettingsScreen::new()),
+                        MenuAction::PuzzlePack(index) => {
+                            let pack = PuzzlePack::load(&config.puzzle_packs[index]);
+                            puzzle_picker = Some(PuzzlePicker::new(pack));
+                        }
+                        MenuAction::Online => {
+                            online = Some(OnlineClient::connect(&server, &player_name))
                         }
-                        _ => {}
                     }
                 }
             }
             GameState::Playing => {
-                if let Some(pos) = is_mouse_left_btn_pressed() {
-                    game.make_move(pos);
+                let shortcuts = [
+                    (Command::Restart, GameCommand::Restart),
+                    (Command::NewBoard, GameCommand::NewBoard),
+                ];
+                if settings.bindings.is_pressed(Command::GameMenu) {
+                    game_menu.toggle();
+                } else if let Some((_, shortcut)) = shortcuts
+                    .iter()
+                    .find(|(key, _)| settings.bindings.is_pressed(*key))
+                {
+                    if game.can_start_again() {
+                        command = game_menu.request(*shortcut, &game);
+                    }
+                }
+
+                if game_menu.is_open() {
+                    // the clicks are for the menu
+                } else if let Some(player) = &mut replay_player {
+                    player.update(&mut game);
+                    if player.is_finished() {
+                        replay_player = None;
+                    }
+                } else if let Some(pos) = is_mouse_left_btn_pressed() {
+                    match settings.bindings.swap_mouse_buttons {
+                        true => game.mark_tile(pos),
+                        false => game.make_move(pos),
+                    }
                 } else if let Some(pos) = is_mouse_right_btn_pressed() {
-                    game.mark_tile(pos);
+                    match settings.bindings.swap_mouse_buttons {
+                        true => game.make_move(pos),
+                        false => game.mark_tile(pos),
+                    }
+                } else if settings.bindings.is_pressed(Command::Hint) {
+                    game.show_hint();
                 }
             }
         }
 
+        diagnostics.measure(Phase::Input);
+
+        let chosen = game_menu.draw(&game);
+        if let Some(command) = command.or(chosen) {
+            let left = match command {
+                GameCommand::Restart => game.restart(),
+                GameCommand::NewBoard => game.start_new_board(),
+                GameCommand::QuitToMenu => {
+                    game.quit();
+                    online = None;
+                    true
+                }
+            };
+            if left {
+                replay_player = None;
+            }
+        }
+
+        if settings.bindings.is_pressed(Command::ExportBoard) {
+            for path in game.export_board() {
+                println!("Board exported to {}", path);
+            }
+        }
+
+        if settings.bindings.is_pressed(Command::NextTheme) {
+            settings.theme = Theme::next(&settings.theme);
+            settings.save();
+            game.set_theme(Theme::from_settings(&settings, &default_theme).await);
+        }
+
+        if settings.bindings.is_pressed(Command::Mute) {
+            settings.volume.muted = !settings.volume.muted;
+            settings.save();
+            game.set_volume(settings.volume);
+        }
+
+        if settings.bindings.is_pressed(Command::NextPalette) {
+            settings.palette = settings.palette.next();
+            settings.save();
+            game.set_theme(Theme::from_settings(&settings, &default_theme).await);
+        }
+
+        if settings.bindings.is_pressed(Command::Diagnostics) {
+            settings.diagnostics = !settings.diagnostics;
+            settings.save();
+            diagnostics.set_visible(settings.diagnostics);
+        }
+        diagnostics.measure(Phase::Update);
+
         game.draw();
-        diagnostics.on_loop();
+        if let Some(client) = &online {
+            write_opponents_progress(client, &game.colors(), &game.grid().footer);
+        }
+
+        diagnostics.measure(Phase::Draw);
+        diagnostics.on_loop(&game);
         next_frame().await
     }
 }
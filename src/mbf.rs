@@ -0,0 +1,162 @@
+//! Minesweeper Board Format (`.mbf`), a mine layout without any moves:
+//!
+//! - `u8` cols, `u8` rows
+//! - `u16` number of mines, big endian
+//! - `u8` col and `u8` row of every mine
+
+use std::{fs, path::Path};
+
+use crate::{
+    replay::{ByteReader, FormatError},
+    tile::Tile,
+    vector3::Vector3,
+};
+
+pub fn read_board(path: impl AsRef<Path>) -> Result<(Vector3<i32>, Vec<Tile>), FormatError> {
+    from_bytes(&fs::read(path)?)
+}
+
+pub fn write_board(
+    path: impl AsRef<Path>,
+    dimensions: Vector3<i32>,
+    tiles: &[Tile],
+) -> Result<(), FormatError> {
+    let bytes = to_bytes(dimensions, tiles)?;
+    if let Some(dir) = path.as_ref().parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    Ok(fs::write(path, bytes)?)
+}
+
+pub fn from_bytes(bytes: &[u8]) -> Result<(Vector3<i32>, Vec<Tile>), FormatError> {
+    let mut reader = ByteReader::new(bytes);
+    let cols = reader.u8()? as i32;
+    let rows = reader.u8()? as i32;
+    let mines = reader.u16()?;
+
+    if rows == 0 || cols == 0 {
+        return Err(FormatError::Invalid("the board has no tiles".into()));
+    }
+
+    let mut tiles: Vec<Tile> = vec![Default::default(); (rows * cols) as usize];
+    for _ in 0..mines {
+        let col = reader.u8()? as i32;
+        let row = reader.u8()? as i32;
+        if row >= rows || col >= cols {
+            return Err(FormatError::Invalid("a mine is outside the board".into()));
+        }
+
+        let tile = &mut tiles[(row * cols + col) as usize];
+        if tile.has_mine {
+            return Err(FormatError::Invalid("two mines on the same tile".into()));
+        }
+        tile.has_mine = true;
+    }
+
+    Ok((Vector3::new(rows, cols, 1), tiles))
+}
+
+pub fn to_bytes(dimensions: Vector3<i32>, tiles: &[Tile]) -> Result<Vec<u8>, FormatError> {
+    let Vector3 {
+        x: rows,
+        y: cols,
+        z: layers,
+    } = dimensions;
+
+    if layers != 1 {
+        return Err(FormatError::Unsupported("layered boards".into()));
+    }
+
+    if !(1..=255).contains(&rows) || !(1..=255).contains(&cols) {
+        return Err(FormatError::Unsupported(format!(
+            "a board of {}x{} tiles",
+            rows, cols
+        )));
+    }
+
+    let mines: Vec<usize> = (0..tiles.len()).filter(|i| tiles[*i].has_mine).collect();
+    let mines_count = u16::try_from(mines.len())
+        .map_err(|_| FormatError::Unsupported(format!("{} mines", mines.len())))?;
+
+    let mut bytes = vec![cols as u8, rows as u8];
+    bytes.extend_from_slice(&mines_count.to_be_bytes());
+    for index in mines {
+        let cols = cols as usize;
+        bytes.push((index % cols) as u8);
+        bytes.push((index / cols) as u8);
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    fn sample_board() -> (Vector3<i32>, Vec<Tile>) {
+        let dimensions = Vector3::new(5, 7, 1);
+        let tiles = (0..35)
+            .map(|index| Tile {
+                has_mine: [0, 6, 17, 34].contains(&index),
+                ..Default::default()
+            })
+            .collect();
+
+        (dimensions, tiles)
+    }
+
+    #[test]
+    fn round_trip() {
+        let (dimensions, tiles) = sample_board();
+        let bytes = to_bytes(dimensions, &tiles).unwrap();
+        assert_eq!(from_bytes(&bytes).unwrap(), (dimensions, tiles));
+    }
+
+    #[test]
+    fn truncated_files_fail() {
+        let (dimensions, tiles) = sample_board();
+        let bytes = to_bytes(dimensions, &tiles).unwrap();
+        for len in 0..bytes.len() {
+            assert!(from_bytes(&bytes[..len]).is_err(), "{} bytes", len);
+        }
+    }
+
+    #[test]
+    fn invalid_boards_fail() {
+        // no tiles
+        assert!(matches!(
+            from_bytes(&[0, 5, 0, 0]),
+            Err(FormatError::Invalid(_))
+        ));
+        // a mine past the last col
+        assert!(matches!(
+            from_bytes(&[3, 3, 0, 1, 3, 0]),
+            Err(FormatError::Invalid(_))
+        ));
+        // the same mine twice
+        assert!(matches!(
+            from_bytes(&[3, 3, 0, 2, 1, 1, 1, 1]),
+            Err(FormatError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn layered_and_large_boards_are_not_written() {
+        let (_, tiles) = sample_board();
+        assert!(to_bytes(Vector3::new(5, 7, 2), &tiles).is_err());
+        assert!(to_bytes(Vector3::new(256, 1, 1), &tiles).is_err());
+    }
+
+    #[test]
+    fn garbage_does_not_panic() {
+        let mut rng = StdRng::seed_from_u64(28);
+        for _ in 0..2000 {
+            let len = rng.gen_range(0..64);
+            let bytes: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            let _ = from_bytes(&bytes);
+        }
+    }
+}
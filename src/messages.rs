@@ -1,61 +1,313 @@
 use macroquad::{
-    color::{BLACK, GREEN, RED},
-    text::draw_text,
+    color::{Color, GRAY, LIGHTGRAY},
+    shapes::draw_rectangle,
+    text::{draw_text, measure_text},
+    window::{screen_height, screen_width},
 };
 
-use crate::{grid::GridSection, utils::get_time_diff};
+use crate::{
+    daily::DailyChallenge,
+    difficulty::Difficulty,
+    grid::{ui_scale, GridSection, Slot},
+    hint::{Hint, Pattern},
+    hot_seat::HotSeat,
+    hud::face_bounds,
+    online::{ClientStatus, OnlineClient, PlayerStatus},
+    puzzle::Puzzle,
+    theme::ThemeColors,
+    utils::get_time_diff,
+};
 
+/// Font size before scaling, see [`font_size`].
 pub const FONT_SIZE: f32 = 20.0;
 
-pub fn write_game_over(grid: &GridSection) {
+/// Font size of the text at the current UI scale.
+pub fn font_size() -> f32 {
+    FONT_SIZE * ui_scale()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Align {
+    /// against the left edge of the slot, or the right edge of the right one
+    In(Slot),
+    /// starting at this x
+    At(f32),
+}
+
+/// Just right of the face in the middle of the header.
+fn beside_face(grid: &GridSection) -> Align {
+    let (pos, size) = face_bounds(grid);
+
+    Align::At(pos.x + size * 1.5)
+}
+
+/// Writes a line of text in the section, centred vertically on its glyphs.
+fn write(text: &str, align: Align, font_size: f32, color: Color, grid: &GridSection) {
     let pos = grid.pos();
     let size = grid.screen_size();
+    let dimensions = measure_text(text, None, font_size as u16, 1.0);
 
-    draw_text(
-        "You lost :(",
-        pos.x + size.x / 2.0 - 10.0,
-        pos.y + size.y / 2.0,
-        FONT_SIZE * 1.2,
-        RED,
+    let x = match align {
+        Align::In(Slot::Right) => {
+            let (x, width) = grid.slot(Slot::Right);
+            x + width - dimensions.width
+        }
+        Align::In(slot) => grid.slot(slot).0,
+        Align::At(x) => x,
+    };
+    let y = pos.y + (size.y - dimensions.height) / 2.0 + dimensions.offset_y;
+
+    draw_text(text, x, y, font_size, color);
+}
+
+/// Writes a line of text in a slot of the section, smaller when it would not
+/// fit, against its left edge or, in the right slot, its right edge.
+fn write_in_slot(text: &str, slot: Slot, color: Color, grid: &GridSection) {
+    let (_, width) = grid.slot(slot);
+
+    write(
+        text,
+        Align::In(slot),
+        fitting_font_size(text, width),
+        color,
+        grid,
     );
 }
 
-pub fn write_you_win(grid: &GridSection) {
-    let pos = grid.pos();
-    let size = grid.screen_size();
+/// The usual font size, or a smaller one for the text to fit in `width`.
+fn fitting_font_size(text: &str, width: f32) -> f32 {
+    let font_size = font_size();
+    let text_width = measure_text(text, None, font_size as u16, 1.0).width;
 
-    draw_text(
+    match text_width > width && text_width > 0.0 {
+        true => (font_size * width / text_width).floor().max(1.0),
+        false => font_size,
+    }
+}
+
+pub fn write_game_over(color: Color, grid: &GridSection) {
+    write(
+        "You lost :(",
+        beside_face(grid),
+        font_size() * 1.2,
+        color,
+        grid,
+    );
+}
+
+pub fn write_you_win(color: Color, grid: &GridSection) {
+    write(
         "You Win!",
-        pos.x + size.x / 2.0 - 10.0,
-        pos.y + size.y / 2.0,
-        FONT_SIZE * 1.2,
-        GREEN,
+        beside_face(grid),
+        font_size() * 1.2,
+        color,
+        grid,
     );
 }
 
-pub fn write_remaining_mines(count: i32, grid: &GridSection) {
-    let pos = grid.pos();
-    let size = grid.screen_size();
+pub fn write_layer_label(layer: i32, x: f32, color: Color, grid: &GridSection) {
+    write(
+        &format!("Layer {}", layer + 1),
+        Align::At(x),
+        font_size(),
+        color,
+        grid,
+    );
+}
+
+pub fn write_daily_label(daily: &DailyChallenge, color: Color, grid: &GridSection) {
+    let kind = match daily.ranked {
+        true => "ranked",
+        false => "practice, unranked",
+    };
+
+    write_in_slot(
+        &format!("Daily {} ({})", daily.date, kind),
+        Slot::Left,
+        color,
+        grid,
+    );
+}
+
+/// Draws the rating of the board in the right slot of the footer.
+pub fn write_difficulty(difficulty: &Difficulty, color: Color, grid: &GridSection) {
+    let text = format!(
+        "{:?}: 3BV {}, {} forced guesses",
+        difficulty.level(),
+        difficulty.three_bv,
+        difficulty.guesses
+    );
+
+    write_in_slot(&text, Slot::Right, color, grid);
+}
+
+pub fn write_puzzle_label(puzzle: &Puzzle, color: Color, grid: &GridSection) {
+    write_in_slot(
+        &format!("Puzzle {}: {} mistakes", puzzle.name, puzzle.mistakes),
+        Slot::Left,
+        color,
+        grid,
+    );
+}
+
+pub fn write_hint(hint: &Hint, color: Color, grid: &GridSection) {
+    let x = grid.pos().x + grid.screen_size().x / 5.0;
+
+    write(&hint.describe(), Align::At(x), font_size(), color, grid);
+}
+
+/// Written where the hint would be when there is none.
+pub fn write_guess_needed(color: Color, grid: &GridSection) {
+    let x = grid.pos().x + grid.screen_size().x / 5.0;
+
+    write(
+        "Nothing is certain, the next move is a guess",
+        Align::At(x),
+        font_size(),
+        color,
+        grid,
+    );
+}
+
+pub fn write_hot_seat_turn(hot_seat: &HotSeat, color: Color, grid: &GridSection) {
+    write(
+        &format!("{}'s turn", hot_seat.current_player().name),
+        beside_face(grid),
+        font_size() * 1.2,
+        color,
+        grid,
+    );
+}
+
+pub fn write_hot_seat_winner(hot_seat: &HotSeat, color: Color, grid: &GridSection) {
+    let leaders = hot_seat.leaders();
+    let text = match leaders.as_slice() {
+        [winner] => format!("{} wins!", winner.name),
+        _ => "It's a draw!".to_string(),
+    };
+
+    write(&text, beside_face(grid), font_size() * 1.2, color, grid);
+}
+
+/// Writes every player's score, the one whose turn it is in the hint colour.
+/// The first half of the players are in the left slot and the others in the
+/// right one, leaving the middle to the minimap.
+pub fn write_hot_seat_scores(hot_seat: &HotSeat, colors: &ThemeColors, grid: &GridSection) {
+    let left_count = hot_seat.players.len().div_ceil(2);
+
+    hot_seat.players.iter().enumerate().for_each(|(i, player)| {
+        let color = match i == hot_seat.current {
+            true => colors.hint,
+            false => colors.text,
+        };
+
+        let (slot, column, columns) = match i < left_count {
+            true => (Slot::Left, i, left_count),
+            false => (
+                Slot::Right,
+                i - left_count,
+                hot_seat.players.len() - left_count,
+            ),
+        };
+        let (x, width) = grid.slot(slot);
+        let column_width = width / columns as f32;
+
+        let text = format!(
+            "{}: {} ({} mines hit)",
+            player.name, player.score, player.mines_hit
+        );
+        write(
+            &text,
+            Align::At(x + column as f32 * column_width),
+            fitting_font_size(&text, column_width),
+            color,
+            grid,
+        );
+    });
+}
+
+pub fn write_waiting_for_opponents(client: &OnlineClient, color: Color) {
+    let text = match client.status {
+        ClientStatus::Disconnected => "Could not reach the server, press Escape",
+        ClientStatus::Connecting => "Connecting to the server... (Escape to cancel)",
+        _ => "Waiting for opponents... (Escape to cancel)",
+    };
+
+    write_in_middle(text, color);
+}
+
+pub fn write_training_search(pattern: Pattern, color: Color) {
+    write_in_middle(
+        &format!(
+            "Looking for a {} position... (Escape to cancel)",
+            pattern.name()
+        ),
+        color,
+    );
+}
+
+/// Writes a line in the middle of the window.
+fn write_in_middle(text: &str, color: Color) {
+    let font_size = font_size();
+    let width = measure_text(text, None, font_size as u16, 1.0).width;
 
     draw_text(
-        &format!("Mines: {}", count),
-        pos.x,
-        pos.y + size.y / 2.0,
-        FONT_SIZE,
-        BLACK,
+        text,
+        (screen_width() - width) / 2.0,
+        screen_height() / 2.0,
+        font_size,
+        color,
     );
 }
 
-pub fn write_time(start_time: i64, end_time: i64, grid: &GridSection) {
-    let (mins, secs) = get_time_diff(start_time, end_time);
+/// Draws a progress bar per opponent in the left slot of the footer, clear
+/// of the counters, face and hints of the header.
+pub fn write_opponents_progress(client: &OnlineClient, colors: &ThemeColors, grid: &GridSection) {
     let pos = grid.pos();
     let size = grid.screen_size();
 
-    draw_text(
-        &format!("{:02}:{:02}", mins, secs),
-        pos.x + size.x - FONT_SIZE * 2.0,
-        pos.y + size.y / 2.0,
-        FONT_SIZE,
-        BLACK,
-    );
+    let (x, slot_width) = grid.slot(Slot::Left);
+    let width = slot_width / 2.0;
+    let count = client.opponents.len().max(1) as f32;
+    let bar_height = size.y / count;
+
+    if client.status == ClientStatus::Disconnected {
+        write_in_slot("Disconnected", Slot::Left, colors.loss, grid);
+        return;
+    }
+
+    client
+        .opponents
+        .values()
+        .enumerate()
+        .for_each(|(i, opponent)| {
+            let y = pos.y + i as f32 * bar_height;
+            let (label, color) = match opponent.status {
+                PlayerStatus::Playing => (format!("{:.0}%", opponent.percent), colors.hint),
+                PlayerStatus::Finished { seconds } => {
+                    let (mins, secs) = get_time_diff(0, seconds);
+                    (format!("{:02}:{:02}", mins, secs), colors.win)
+                }
+                PlayerStatus::Lost => ("lost".to_string(), colors.loss),
+                PlayerStatus::Disconnected => ("left".to_string(), GRAY),
+            };
+            let text = format!("{} {}", opponent.name, label);
+            let gap = 5.0 * ui_scale();
+
+            draw_rectangle(x, y, width, bar_height - 2.0, LIGHTGRAY);
+            draw_rectangle(
+                x,
+                y,
+                width * opponent.percent / 100.0,
+                bar_height - 2.0,
+                color,
+            );
+            draw_text(
+                &text,
+                x + width + gap,
+                y + bar_height / 2.0 + font_size() / 4.0,
+                (bar_height * 0.8).min(fitting_font_size(&text, slot_width - width - gap)),
+                colors.text,
+            );
+        });
 }
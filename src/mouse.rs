@@ -10,10 +10,14 @@ pub fn is_mouse_right_btn_pressed() -> Option<Vector2<f32>> {
     is_mouse_pressed(MouseButton::Right)
 }
 
+pub fn cursor_position() -> Vector2<f32> {
+    let (x, y) = mouse_position();
+    Vector2::new(x, y)
+}
+
 fn is_mouse_pressed(button: MouseButton) -> Option<Vector2<f32>> {
     if is_mouse_button_pressed(button) {
-        let (x, y) = mouse_position();
-        Some(Vector2::new(x, y))
+        Some(cursor_position())
     } else {
         None
     }
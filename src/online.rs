@@ -0,0 +1,377 @@
+//! Head-to-head races through a server. Clients and the server exchange
+//! newline-delimited JSON messages over TCP: the server hands out the same
+//! seeded board to every player and relays their progress to the others.
+//!
+//! The server never sees the moves: it times the races itself, but takes the
+//! progress and the outcome each client reports on trust, so races are for
+//! friends rather than rankings.
+
+use std::{
+    collections::BTreeMap,
+    io::{self, ErrorKind, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Game, GameState};
+
+pub const DEFAULT_SERVER: &str = "127.0.0.1:7878";
+
+/// How long to try reaching the server before giving up.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ClientMessage {
+    Join {
+        name: String,
+    },
+    Progress {
+        percent: f32,
+    },
+    /// `seconds` is the client's own time, the server keeps its own
+    Finished {
+        seconds: i64,
+    },
+    Lost,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ServerMessage {
+    Welcome { player_id: u32, waiting_for: usize },
+    Start(StartInfo),
+    Opponent(OpponentProgress),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StartInfo {
+    pub seed: u64,
+    pub rows: i32,
+    pub cols: i32,
+    pub mines: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PlayerStatus {
+    Playing,
+    Finished { seconds: i64 },
+    Lost,
+    Disconnected,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpponentProgress {
+    pub player_id: u32,
+    pub name: String,
+    pub percent: f32,
+    pub status: PlayerStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClientStatus {
+    /// reaching the server in the background
+    Connecting,
+    /// joined, waiting for the other players
+    Waiting,
+    Playing,
+    /// our result was sent, the opponents may still be playing
+    Done,
+    /// the connection to the server was lost
+    Disconnected,
+}
+
+/// Writes a message as a single JSON line.
+pub fn write_message(stream: &mut impl Write, message: &impl Serialize) -> io::Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+}
+
+#[derive(Debug)]
+pub struct OnlineClient {
+    /// none until the connection is made
+    stream: Option<TcpStream>,
+    connecting: Option<Receiver<io::Result<TcpStream>>>,
+    buffer: Vec<u8>,
+    /// messages not yet taken by the socket
+    outgoing: Vec<u8>,
+    last_percent: f32,
+    pub status: ClientStatus,
+    pub opponents: BTreeMap<u32, OpponentProgress>,
+}
+
+impl OnlineClient {
+    /// Starts joining the server in the background, so the window keeps
+    /// drawing while it is reached. [`OnlineClient::poll`] picks it up.
+    pub fn connect(addr: &str, name: &str) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let addr = addr.to_string();
+        let join = ClientMessage::Join {
+            name: name.to_string(),
+        };
+        thread::spawn(move || {
+            let _ = sender.send(join_server(&addr, &join));
+        });
+
+        OnlineClient {
+            stream: None,
+            connecting: Some(receiver),
+            buffer: Vec::new(),
+            outgoing: Vec::new(),
+            last_percent: -1.0,
+            status: ClientStatus::Connecting,
+            opponents: BTreeMap::new(),
+        }
+    }
+
+    /// Reads the messages received since the last call and returns the board
+    /// to start when the race begins.
+    pub fn poll(&mut self) -> Option<StartInfo> {
+        self.check_connection();
+        if self.status == ClientStatus::Disconnected {
+            return None;
+        }
+        self.flush();
+        let Some(stream) = &mut self.stream else {
+            return None;
+        };
+
+        let mut chunk = [0; 1024];
+        let connected = loop {
+            match stream.read(&mut chunk) {
+                Ok(0) => break false,
+                Ok(len) => self.buffer.extend_from_slice(&chunk[..len]),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break true,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => break false,
+            }
+        };
+        if !connected {
+            self.disconnect();
+        }
+
+        let mut start = None;
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            match serde_json::from_slice::<ServerMessage>(&line) {
+                Ok(ServerMessage::Start(info)) => {
+                    self.status = ClientStatus::Playing;
+                    self.last_percent = -1.0;
+                    start = Some(info);
+                }
+                Ok(ServerMessage::Opponent(progress)) => {
+                    self.opponents.insert(progress.player_id, progress);
+                }
+                Ok(ServerMessage::Welcome { .. }) => {}
+                Err(err) => eprintln!("Ignoring a message from the server: {}", err),
+            }
+        }
+
+        start
+    }
+
+    /// Sends the progress of the local game, and its result once it ends.
+    pub fn report(&mut self, game: &Game) {
+        if self.status != ClientStatus::Playing {
+            return;
+        }
+
+        let message = match game.get_state() {
+            GameState::Playing => {
+                let percent = game.progress();
+                if percent == self.last_percent {
+                    return;
+                }
+                self.last_percent = percent;
+                ClientMessage::Progress { percent }
+            }
+            GameState::GameWon => ClientMessage::Finished {
+                seconds: game.end_time - game.start_time,
+            },
+            GameState::GameOver => ClientMessage::Lost,
+            GameState::NotStarted => return,
+        };
+
+        if matches!(
+            message,
+            ClientMessage::Finished { .. } | ClientMessage::Lost
+        ) {
+            self.status = ClientStatus::Done;
+        }
+
+        if write_message(&mut self.outgoing, &message).is_ok() {
+            self.flush();
+        }
+    }
+
+    /// Takes the stream once the background connection is made.
+    fn check_connection(&mut self) {
+        let Some(receiver) = &self.connecting else {
+            return;
+        };
+
+        match receiver.try_recv() {
+            Ok(Ok(stream)) => {
+                self.connecting = None;
+                self.stream = Some(stream);
+                self.status = ClientStatus::Waiting;
+            }
+            Ok(Err(err)) => {
+                eprintln!("Could not connect to the server: {}", err);
+                self.connecting = None;
+                self.status = ClientStatus::Disconnected;
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => {
+                self.connecting = None;
+                self.disconnect();
+            }
+        }
+    }
+
+    /// Writes as much of the outgoing messages as the socket takes without
+    /// waiting, keeping the rest for the next frame.
+    fn flush(&mut self) {
+        if self.status == ClientStatus::Disconnected {
+            return;
+        }
+        let Some(stream) = &mut self.stream else {
+            return;
+        };
+
+        let connected = loop {
+            if self.outgoing.is_empty() {
+                break true;
+            }
+            match stream.write(&self.outgoing) {
+                Ok(0) => break false,
+                Ok(len) => {
+                    self.outgoing.drain(..len);
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break true,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => break false,
+            }
+        };
+        if !connected {
+            self.disconnect();
+        }
+    }
+
+    /// Drops the connection, saying so only the first time.
+    fn disconnect(&mut self) {
+        if self.status != ClientStatus::Disconnected {
+            eprintln!("Lost the connection to the server");
+        }
+        self.status = ClientStatus::Disconnected;
+        self.stream = None;
+        self.outgoing.clear();
+    }
+}
+
+/// Connects to the server and joins, then leaves the stream non-blocking.
+fn join_server(addr: &str, join: &ClientMessage) -> io::Result<TcpStream> {
+    let addr = addr
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "no address for the server"))?;
+    let mut stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+    write_message(&mut stream, join)?;
+    stream.set_nonblocking(true)?;
+
+    Ok(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader},
+        net::TcpListener,
+        time::Instant,
+    };
+
+    use super::*;
+
+    /// Polls until the client leaves `status` or a second has passed.
+    fn poll_while(client: &mut OnlineClient, status: ClientStatus) -> Option<StartInfo> {
+        let started = Instant::now();
+        while client.status == status && started.elapsed() < Duration::from_secs(1) {
+            if let Some(start) = client.poll() {
+                return Some(start);
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        None
+    }
+
+    #[test]
+    fn connects_in_the_background_and_joins() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        let mut client = OnlineClient::connect(&addr, "alice");
+        assert_eq!(client.status, ClientStatus::Connecting);
+
+        let (stream, _) = listener.accept().unwrap();
+        let mut server = stream.try_clone().unwrap();
+        let mut join = String::new();
+        BufReader::new(stream).read_line(&mut join).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ClientMessage>(&join).unwrap(),
+            ClientMessage::Join {
+                name: "alice".to_string()
+            }
+        );
+
+        poll_while(&mut client, ClientStatus::Connecting);
+        assert_eq!(client.status, ClientStatus::Waiting);
+
+        let start = StartInfo {
+            seed: 7,
+            rows: 9,
+            cols: 9,
+            mines: 10,
+        };
+        write_message(&mut server, &ServerMessage::Start(start)).unwrap();
+        assert_eq!(poll_while(&mut client, ClientStatus::Waiting), Some(start));
+        assert_eq!(client.status, ClientStatus::Playing);
+    }
+
+    #[test]
+    fn an_unreachable_server_disconnects() {
+        // the port is free again once the listener is dropped
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string();
+
+        let mut client = OnlineClient::connect(&addr, "alice");
+        poll_while(&mut client, ClientStatus::Connecting);
+        assert_eq!(client.status, ClientStatus::Disconnected);
+    }
+
+    #[test]
+    fn a_closed_connection_is_dropped_once() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        let mut client = OnlineClient::connect(&addr, "alice");
+        drop(listener.accept().unwrap());
+        poll_while(&mut client, ClientStatus::Connecting);
+        poll_while(&mut client, ClientStatus::Waiting);
+        assert_eq!(client.status, ClientStatus::Disconnected);
+        assert!(client.stream.is_none());
+
+        write_message(&mut client.outgoing, &ClientMessage::Lost).unwrap();
+        assert_eq!(client.poll(), None);
+        assert_eq!(client.status, ClientStatus::Disconnected);
+        assert!(!client.outgoing.is_empty());
+    }
+}
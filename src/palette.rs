@@ -0,0 +1,191 @@
+//! Palettes for colour-blind players, laid over the colours of the theme.
+
+use macroquad::color::{Color, BLACK, WHITE};
+use serde::{Deserialize, Serialize};
+
+use crate::theme::{Theme, ThemeColors};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Palette {
+    /// the colours of the theme
+    #[default]
+    Standard,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+    HighContrast,
+}
+
+// colours that stay apart for every kind of colour blindness, after Okabe and Ito
+const ORANGE: Color = Color::from_hex(0xe69f00);
+const SKY_BLUE: Color = Color::from_hex(0x56b4e9);
+const BLUISH_GREEN: Color = Color::from_hex(0x009e73);
+const YELLOW: Color = Color::from_hex(0xf0e442);
+const BLUE: Color = Color::from_hex(0x0072b2);
+const VERMILLION: Color = Color::from_hex(0xd55e00);
+const PURPLE: Color = Color::from_hex(0xcc79a7);
+const DARK_GRAY: Color = Color::from_hex(0x404040);
+const GRAY: Color = Color::from_hex(0x808080);
+const LIGHT_GRAY: Color = Color::from_hex(0xd9d9d9);
+
+impl Palette {
+    pub const ALL: [Palette; 5] = [
+        Palette::Standard,
+        Palette::Deuteranopia,
+        Palette::Protanopia,
+        Palette::Tritanopia,
+        Palette::HighContrast,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Palette::Standard => "standard",
+            Palette::Deuteranopia => "deuteranopia",
+            Palette::Protanopia => "protanopia",
+            Palette::Tritanopia => "tritanopia",
+            Palette::HighContrast => "high contrast",
+        }
+    }
+
+    pub fn next(&self) -> Palette {
+        let index = Palette::ALL.iter().position(|palette| palette == self);
+        Palette::ALL[index.map_or(0, |index| (index + 1) % Palette::ALL.len())]
+    }
+
+    /// Replaces the colours of the theme and turns on the shape cues. The
+    /// tile textures are dropped so the palette's colours show.
+    pub fn apply(&self, theme: &mut Theme) {
+        let Some((colors, numbers)) = self.colors() else {
+            return;
+        };
+
+        theme.colors = colors;
+        theme.numbers = numbers;
+        theme.textures.hidden = None;
+        theme.textures.revealed = None;
+        theme.shape_cues = true;
+    }
+
+    /// The colours and the number colours of the palette, none for the
+    /// theme's own.
+    fn colors(&self) -> Option<(ThemeColors, Vec<Color>)> {
+        let palette = match self {
+            Palette::Standard => return None,
+            // red and green look alike, so mines and losses are orange and wins blue
+            Palette::Deuteranopia => (
+                ThemeColors {
+                    background: WHITE,
+                    hidden: SKY_BLUE,
+                    revealed: LIGHT_GRAY,
+                    mine_background: ORANGE,
+                    flag_background: SKY_BLUE,
+                    text: BLACK,
+                    win: BLUE,
+                    loss: ORANGE,
+                    hint: PURPLE,
+                    partial: YELLOW,
+                },
+                vec![
+                    BLUE,
+                    ORANGE,
+                    BLACK,
+                    PURPLE,
+                    DARK_GRAY,
+                    BLUISH_GREEN,
+                    VERMILLION,
+                    GRAY,
+                ],
+            ),
+            // red also looks dark, so the mines are yellow
+            Palette::Protanopia => (
+                ThemeColors {
+                    background: WHITE,
+                    hidden: SKY_BLUE,
+                    revealed: LIGHT_GRAY,
+                    mine_background: YELLOW,
+                    flag_background: SKY_BLUE,
+                    text: BLACK,
+                    win: BLUE,
+                    loss: VERMILLION,
+                    hint: PURPLE,
+                    partial: YELLOW,
+                },
+                vec![
+                    BLUE,
+                    ORANGE,
+                    BLACK,
+                    PURPLE,
+                    DARK_GRAY,
+                    BLUISH_GREEN,
+                    VERMILLION,
+                    GRAY,
+                ],
+            ),
+            // blue and green look alike, as do yellow and violet
+            Palette::Tritanopia => (
+                ThemeColors {
+                    background: WHITE,
+                    hidden: GRAY,
+                    revealed: LIGHT_GRAY,
+                    mine_background: VERMILLION,
+                    flag_background: GRAY,
+                    text: BLACK,
+                    win: BLUISH_GREEN,
+                    loss: VERMILLION,
+                    hint: PURPLE,
+                    partial: ORANGE,
+                },
+                vec![
+                    BLUISH_GREEN,
+                    VERMILLION,
+                    BLACK,
+                    PURPLE,
+                    DARK_GRAY,
+                    BLUE,
+                    ORANGE,
+                    GRAY,
+                ],
+            ),
+            Palette::HighContrast => (
+                ThemeColors {
+                    background: WHITE,
+                    hidden: BLACK,
+                    revealed: WHITE,
+                    mine_background: Color::from_hex(0xffff00),
+                    flag_background: Color::from_hex(0xffff00),
+                    text: BLACK,
+                    // outlines must show on black hidden tiles as on white ones
+                    win: Color::from_hex(0x0080ff),
+                    loss: Color::from_hex(0xff00ff),
+                    hint: Color::from_hex(0xff8000),
+                    partial: Color::from_hex(0xffff00),
+                },
+                vec![BLACK; 8],
+            ),
+        };
+
+        Some(palette)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colour_blind_palettes_give_each_number_its_colour() {
+        for palette in [
+            Palette::Deuteranopia,
+            Palette::Protanopia,
+            Palette::Tritanopia,
+        ] {
+            let (_, numbers) = palette.colors().unwrap();
+            for (i, a) in numbers.iter().enumerate() {
+                for (j, b) in numbers.iter().enumerate().skip(i + 1) {
+                    assert_ne!(a, b, "{} and {} of {}", i + 1, j + 1, palette.name());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,331 @@
+//! Puzzles are partially revealed boards where everything the solver can
+//! deduce has to be resolved without guessing. They are scored on the
+//! mistakes made rather than on time.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ffi::OsStr,
+    fs,
+    path::Path,
+};
+
+use macroquad::{
+    color::{Color, LIGHTGRAY},
+    shapes::draw_rectangle,
+    text::draw_text,
+    window::{screen_height, screen_width},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bot::{BotAction, Solver, VisibleTile},
+    config::PuzzlePackConfig,
+    game::{Game, GameState},
+    grid::ui_scale,
+    messages::font_size,
+    theme::ThemeColors,
+    tile::{Tile, TileState},
+    utils::load_json,
+    vector2::Vector2,
+    vector3::Vector3,
+};
+
+const PROGRESS_FILE: &str = "./puzzles.json";
+
+const CELL_SIZE: f32 = 60.0;
+const CELL_PADDING: f32 = 4.0;
+const CELLS_PER_ROW: usize = 8;
+
+/// The hidden tiles the solver can resolve from the start of the puzzle,
+/// each one possibly only after others are resolved.
+pub fn deducible(dimensions: Vector3<i32>, tiles: &[Tile]) -> BTreeSet<usize> {
+    let mut game = Game::headless();
+    game.start_with_tiles(dimensions, tiles.to_vec());
+
+    while game.get_state() == GameState::Playing {
+        match Solver::solve(&game.visible_board()) {
+            (BotAction::Reveal(pos), Some(_)) => game.reveal(pos),
+            (BotAction::Flag(pos), Some(_)) => game.toggle_flag(pos),
+            _ => break,
+        }
+    }
+
+    game.visible_board()
+        .tiles
+        .iter()
+        .enumerate()
+        .filter(|(index, tile)| {
+            tiles[*index].state == TileState::Hidden && **tile != VisibleTile::Hidden
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Puzzle {
+    pub name: String,
+    /// file of the puzzle, none for the generated training ones
+    pub path: Option<String>,
+    pub mistakes: i32,
+    targets: BTreeSet<usize>,
+}
+
+impl Puzzle {
+    pub fn new(path: &str, targets: BTreeSet<usize>) -> Self {
+        Puzzle {
+            name: puzzle_name(path),
+            path: Some(path.to_string()),
+            mistakes: 0,
+            targets,
+        }
+    }
+
+    /// A generated position that isn't kept in the progress.
+    pub fn training(name: &str, targets: BTreeSet<usize>) -> Self {
+        Puzzle {
+            name: name.to_string(),
+            path: None,
+            mistakes: 0,
+            targets,
+        }
+    }
+
+    /// Revealing a mine, or a tile that can't be deduced yet, is a mistake.
+    pub fn check_reveal(&mut self, index: usize, has_mine: bool) {
+        if has_mine || !self.targets.contains(&index) {
+            self.mistakes += 1;
+        }
+    }
+
+    pub fn check_flag(&mut self, has_mine: bool) {
+        if !has_mine {
+            self.mistakes += 1;
+        }
+    }
+
+    pub fn is_solved(&self, tiles: &[Tile]) -> bool {
+        self.targets.iter().all(|index| {
+            let tile = &tiles[*index];
+            match tile.has_mine {
+                true => tile.state != TileState::Hidden,
+                false => tile.state == TileState::Revealed,
+            }
+        })
+    }
+}
+
+fn puzzle_name(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .and_then(OsStr::to_str)
+        .unwrap_or(path)
+        .to_string()
+}
+
+/// The puzzle files of a pack directory, in name order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PuzzlePack {
+    pub name: String,
+    pub puzzles: Vec<String>,
+}
+
+impl PuzzlePack {
+    pub fn load(config: &PuzzlePackConfig) -> Self {
+        let mut puzzles: Vec<String> = match fs::read_dir(&config.dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    matches!(
+                        path.extension().and_then(OsStr::to_str),
+                        Some("txt") | Some("mbf")
+                    )
+                })
+                .map(|path| path.to_string_lossy().to_string())
+                .collect(),
+            Err(err) => {
+                eprintln!("Could not read the puzzle pack {}: {}", config.dir, err);
+                Vec::new()
+            }
+        };
+        puzzles.sort();
+
+        PuzzlePack {
+            name: config.name.clone(),
+            puzzles,
+        }
+    }
+}
+
+/// Fewest mistakes of every solved puzzle, keyed by path.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PuzzleProgress {
+    pub solved: BTreeMap<String, i32>,
+}
+
+impl PuzzleProgress {
+    pub fn load() -> Self {
+        load_json(PROGRESS_FILE)
+    }
+
+    pub fn finish(puzzle: &Puzzle) {
+        let Some(path) = &puzzle.path else {
+            return;
+        };
+
+        let mut progress = PuzzleProgress::load();
+        let best = progress
+            .solved
+            .get(path)
+            .map_or(puzzle.mistakes, |best| puzzle.mistakes.min(*best));
+        progress.solved.insert(path.clone(), best);
+
+        let saved = serde_json::to_string_pretty(&progress)
+            .map_err(|err| err.to_string())
+            .and_then(|json| fs::write(PROGRESS_FILE, json).map_err(|err| err.to_string()));
+
+        if let Err(err) = saved {
+            eprintln!("Could not save the puzzle progress: {}", err);
+        }
+    }
+}
+
+/// Grid of the puzzles of a pack, coloured by completion.
+#[derive(Debug)]
+pub struct PuzzlePicker {
+    pack: PuzzlePack,
+    progress: PuzzleProgress,
+}
+
+impl PuzzlePicker {
+    pub fn new(pack: PuzzlePack) -> Self {
+        PuzzlePicker {
+            pack,
+            progress: PuzzleProgress::load(),
+        }
+    }
+
+    pub fn draw(&self, colors: &ThemeColors) {
+        let (left, top) = self.origin();
+        let font_size = font_size();
+        let solved = self
+            .pack
+            .puzzles
+            .iter()
+            .filter(|path| self.progress.solved.contains_key(*path))
+            .count();
+
+        draw_text(
+            format!(
+                "{}: {}/{} solved  (Escape to go back)",
+                self.pack.name,
+                solved,
+                self.pack.puzzles.len()
+            ),
+            left,
+            top - font_size,
+            font_size * 1.2,
+            colors.text,
+        );
+
+        for (i, path) in self.pack.puzzles.iter().enumerate() {
+            let pos = self.cell_position(i);
+            draw_rectangle(
+                pos.x,
+                pos.y,
+                cell_size() - cell_padding(),
+                cell_size() - cell_padding(),
+                self.color(path, colors),
+            );
+            draw_text(
+                (i + 1).to_string(),
+                pos.x + cell_padding(),
+                pos.y + font_size,
+                font_size,
+                colors.text,
+            );
+            draw_text(
+                puzzle_name(path),
+                pos.x + cell_padding(),
+                pos.y + cell_size() - font_size,
+                font_size * 0.6,
+                colors.text,
+            );
+        }
+    }
+
+    /// Path of the puzzle under `pos`.
+    pub fn handle_input(&self, pos: Vector2<f32>) -> Option<String> {
+        self.pack
+            .puzzles
+            .iter()
+            .enumerate()
+            .find(|(i, _)| {
+                let cell = self.cell_position(*i);
+                pos.x >= cell.x
+                    && pos.y >= cell.y
+                    && pos.x <= cell.x + cell_size() - cell_padding()
+                    && pos.y <= cell.y + cell_size() - cell_padding()
+            })
+            .map(|(_, path)| path.clone())
+    }
+
+    fn origin(&self) -> (f32, f32) {
+        let rows = self.pack.puzzles.len().div_ceil(CELLS_PER_ROW).max(1);
+        let left = (screen_width() - cell_size() * CELLS_PER_ROW as f32) / 2.0;
+        let top = (screen_height() - cell_size() * rows as f32) / 2.0;
+
+        (left, top)
+    }
+
+    fn cell_position(&self, index: usize) -> Vector2<f32> {
+        let (left, top) = self.origin();
+
+        Vector2::new(
+            left + (index % CELLS_PER_ROW) as f32 * cell_size(),
+            top + (index / CELLS_PER_ROW) as f32 * cell_size(),
+        )
+    }
+
+    fn color(&self, path: &str, colors: &ThemeColors) -> Color {
+        match self.progress.solved.get(path) {
+            Some(0) => colors.win,
+            Some(_) => colors.partial,
+            None => LIGHTGRAY,
+        }
+    }
+}
+
+fn cell_size() -> f32 {
+    CELL_SIZE * ui_scale()
+}
+
+fn cell_padding() -> f32 {
+    CELL_PADDING * ui_scale()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board_text::read_board;
+
+    use super::*;
+
+    #[test]
+    fn basics_are_solved_without_guessing() {
+        let pack = PuzzlePack::load(&PuzzlePackConfig {
+            name: "Basics".to_string(),
+            dir: "puzzles/basics".to_string(),
+        });
+        assert!(!pack.puzzles.is_empty());
+
+        for path in &pack.puzzles {
+            let (dimensions, tiles) = read_board(path).unwrap();
+            let hidden = tiles
+                .iter()
+                .filter(|tile| tile.state == TileState::Hidden)
+                .count();
+
+            assert_eq!(deducible(dimensions, &tiles).len(), hidden, "{}", path);
+        }
+    }
+}
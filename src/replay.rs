@@ -0,0 +1,260 @@
+use std::{error::Error, ffi::OsStr, fmt, io, path::Path};
+
+use crate::{game::Game, utils::current_time_millis, vector2::Vector2, vector3::Vector3};
+
+#[derive(Debug)]
+pub enum FormatError {
+    Io(io::Error),
+    UnexpectedEof,
+    BadMagic,
+    Unsupported(String),
+    Invalid(String),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::Io(err) => write!(f, "{}", err),
+            FormatError::UnexpectedEof => write!(f, "the file ends unexpectedly"),
+            FormatError::BadMagic => write!(f, "the file has an unknown format"),
+            FormatError::Unsupported(reason) => write!(f, "unsupported: {}", reason),
+            FormatError::Invalid(reason) => write!(f, "invalid file: {}", reason),
+        }
+    }
+}
+
+impl Error for FormatError {}
+
+impl From<io::Error> for FormatError {
+    fn from(err: io::Error) -> Self {
+        FormatError::Io(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayAction {
+    Reveal,
+    Flag,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayEvent {
+    /// milliseconds since the start of the game
+    pub time_millis: u32,
+    pub action: ReplayAction,
+    /// row and col of the tile
+    pub pos: Vector2<i32>,
+}
+
+/// A recorded game on a single layer board.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub rows: i32,
+    pub cols: i32,
+    /// row and col of every mine
+    pub mines: Vec<Vector2<i32>>,
+    pub events: Vec<ReplayEvent>,
+    pub player: String,
+}
+
+impl Replay {
+    pub fn validate(&self) -> Result<(), FormatError> {
+        if !(1..=255).contains(&self.rows) || !(1..=255).contains(&self.cols) {
+            return Err(FormatError::Unsupported(format!(
+                "a board of {}x{} tiles",
+                self.rows, self.cols
+            )));
+        }
+
+        let within_bounds =
+            |pos: &Vector2<i32>| pos.x >= 0 && pos.y >= 0 && pos.x < self.rows && pos.y < self.cols;
+
+        if !self.mines.iter().all(within_bounds) {
+            return Err(FormatError::Invalid("a mine is outside the board".into()));
+        }
+
+        if !self
+            .events
+            .iter()
+            .map(|event| &event.pos)
+            .all(within_bounds)
+        {
+            return Err(FormatError::Invalid("a click is outside the board".into()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Loads a replay file. No replay format can be read yet: readers of Arbiter
+/// `.avf` and Minesweeper X `.rmv` files need sample files from those tools to
+/// be checked against, so every file is refused until they are written.
+pub fn load_replay(path: &str) -> Result<Replay, FormatError> {
+    let format = match Path::new(path).extension().and_then(OsStr::to_str) {
+        Some("avf") => "Arbiter replays",
+        Some("rmv") => "Minesweeper X replays",
+        _ => "replays",
+    };
+
+    Err(FormatError::Unsupported(format!(
+        "{} can't be read yet, {}",
+        format, path
+    )))
+}
+
+/// Big endian reader over the bytes of a file that fails instead of panicking
+/// when the data runs out.
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        ByteReader { bytes, offset: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offset >= self.bytes.len()
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], FormatError> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(FormatError::UnexpectedEof)?;
+
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, FormatError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, FormatError> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+}
+
+/// Plays the events of a replay on a game as time goes by.
+pub struct ReplayPlayer {
+    events: Vec<ReplayEvent>,
+    next_event: usize,
+    start_millis: i64,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: &Replay, game: &mut Game) -> Self {
+        game.start_replay(replay);
+
+        ReplayPlayer {
+            events: replay.events.clone(),
+            next_event: 0,
+            start_millis: current_time_millis(),
+        }
+    }
+
+    pub fn update(&mut self, game: &mut Game) {
+        let elapsed = current_time_millis() - self.start_millis;
+
+        while let Some(event) = self.events.get(self.next_event) {
+            if event.time_millis as i64 > elapsed {
+                break;
+            }
+
+            let pos = Vector3::new(event.pos.x, event.pos.y, 0);
+            match event.action {
+                ReplayAction::Reveal => game.reveal(pos),
+                ReplayAction::Flag => game.toggle_flag(pos),
+            }
+            self.next_event += 1;
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next_event >= self.events.len()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A short game on a custom board.
+    pub(crate) fn sample_replay() -> Replay {
+        let event = |time_millis, action, row, col| ReplayEvent {
+            time_millis,
+            action,
+            pos: Vector2::new(row, col),
+        };
+
+        Replay {
+            rows: 9,
+            cols: 12,
+            mines: vec![Vector2::new(0, 0), Vector2::new(3, 5), Vector2::new(8, 11)],
+            events: vec![
+                event(0, ReplayAction::Reveal, 4, 4),
+                event(1500, ReplayAction::Flag, 3, 5),
+                event(2750, ReplayAction::Reveal, 8, 0),
+            ],
+            player: "tester".into(),
+        }
+    }
+
+    #[test]
+    fn validate_accepts_a_board_within_bounds() {
+        assert!(sample_replay().validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_events_outside_the_board() {
+        for pos in [
+            Vector2::new(9, 0),
+            Vector2::new(0, 12),
+            Vector2::new(-1, 3),
+            Vector2::new(2, -1),
+        ] {
+            let mut replay = sample_replay();
+            replay.events[1].pos = pos;
+            assert!(matches!(replay.validate(), Err(FormatError::Invalid(_))));
+        }
+    }
+
+    #[test]
+    fn validate_rejects_mines_outside_the_board() {
+        let mut replay = sample_replay();
+        replay.mines.push(Vector2::new(9, 12));
+        assert!(matches!(replay.validate(), Err(FormatError::Invalid(_))));
+    }
+
+    #[test]
+    fn validate_rejects_boards_too_large_or_empty() {
+        for (rows, cols) in [(0, 12), (9, 0), (256, 12), (9, 256)] {
+            let replay = Replay {
+                rows,
+                cols,
+                mines: Vec::new(),
+                events: Vec::new(),
+                player: String::new(),
+            };
+            assert!(matches!(
+                replay.validate(),
+                Err(FormatError::Unsupported(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn byte_reader_fails_past_the_end() {
+        let mut reader = ByteReader::new(&[1, 2, 3]);
+        assert_eq!(reader.u16().unwrap(), 0x0102);
+        assert!(matches!(reader.u16(), Err(FormatError::UnexpectedEof)));
+        assert_eq!(reader.u8().unwrap(), 3);
+        assert!(reader.is_empty());
+        assert!(matches!(reader.u8(), Err(FormatError::UnexpectedEof)));
+    }
+}
@@ -0,0 +1,58 @@
+//! Variants of the rules a player can choose in the settings. Games without a
+//! window, like the bots and the server, always play the default ones.
+
+use serde::{Deserialize, Serialize};
+
+/// What clicking a revealed number does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChordRule {
+    /// reveals the neighbours only when the flags around are all right
+    #[default]
+    Safe,
+    /// reveals the neighbours when there are as many flags as the number, a
+    /// wrong flag means revealing a mine
+    Classic,
+    Off,
+}
+
+/// When a game is won.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WinRule {
+    /// every safe tile revealed and every mine flagged
+    #[default]
+    FlagMines,
+    /// every safe tile revealed, the mines are flagged for the player
+    RevealSafe,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rules {
+    pub chord: ChordRule,
+    pub win: WinRule,
+}
+
+impl ChordRule {
+    pub const ALL: [ChordRule; 3] = [ChordRule::Safe, ChordRule::Classic, ChordRule::Off];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChordRule::Safe => "safe",
+            ChordRule::Classic => "classic",
+            ChordRule::Off => "off",
+        }
+    }
+}
+
+impl WinRule {
+    pub const ALL: [WinRule; 2] = [WinRule::FlagMines, WinRule::RevealSafe];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WinRule::FlagMines => "flag every mine",
+            WinRule::RevealSafe => "reveal every safe tile",
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    bindings::Bindings, palette::Palette, rules::Rules, sound::Volume, theme::DEFAULT_THEME,
+};
+
+const SETTINGS_FILE: &str = "./settings.json";
+const SETTINGS_BACKUP_FILE: &str = "./settings.json.bak";
+
+/// How the time of a game is shown in the header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimerDisplay {
+    /// minutes and seconds
+    #[default]
+    Clock,
+    Seconds,
+    Hidden,
+}
+
+impl TimerDisplay {
+    pub const ALL: [TimerDisplay; 3] = [
+        TimerDisplay::Clock,
+        TimerDisplay::Seconds,
+        TimerDisplay::Hidden,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TimerDisplay::Clock => "mm:ss",
+            TimerDisplay::Seconds => "seconds",
+            TimerDisplay::Hidden => "hidden",
+        }
+    }
+}
+
+/// Preferences of the player, apart from the levels in `config.json` that
+/// are shared. Missing entries take their default.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// directory name in `themes`, or the built-in "default"
+    pub theme: String,
+    pub palette: Palette,
+    pub animations: bool,
+    pub volume: Volume,
+    pub bindings: Bindings,
+    pub rules: Rules,
+    pub timer: TimerDisplay,
+    /// the diagnostics overlay, off by default in release builds
+    pub diagnostics: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            theme: DEFAULT_THEME.to_string(),
+            palette: Palette::Standard,
+            animations: true,
+            volume: Volume::default(),
+            bindings: Bindings::default(),
+            rules: Rules::default(),
+            timer: TimerDisplay::Clock,
+            diagnostics: cfg!(debug_assertions),
+        }
+    }
+}
+
+impl Settings {
+    /// Reads the settings file, keeping the entries that parse. When some
+    /// don't, the file is copied aside first, as the next save drops them.
+    pub fn load() -> Self {
+        let Ok(text) = fs::read_to_string(SETTINGS_FILE) else {
+            return Settings::default();
+        };
+
+        let (settings, problems) = Settings::parse(&text);
+        if !problems.is_empty() {
+            for problem in &problems {
+                eprintln!("{}: {}", SETTINGS_FILE, problem);
+            }
+            match fs::copy(SETTINGS_FILE, SETTINGS_BACKUP_FILE) {
+                Ok(_) => eprintln!("The previous settings are kept in {}", SETTINGS_BACKUP_FILE),
+                Err(err) => eprintln!("Could not keep the previous settings: {}", err),
+            }
+        }
+
+        settings
+    }
+
+    /// Parses the settings entry by entry, so one that is wrong only falls
+    /// back to its default. Returns what was wrong along with them.
+    fn parse(text: &str) -> (Settings, Vec<String>) {
+        let entries: Map<String, Value> = match serde_json::from_str(text) {
+            Ok(entries) => entries,
+            Err(err) => return (Settings::default(), vec![err.to_string()]),
+        };
+
+        let mut problems = Vec::new();
+        let mut kept = Map::new();
+        for (key, value) in entries {
+            let mut with_entry = kept.clone();
+            with_entry.insert(key.clone(), value);
+            match serde_json::from_value::<Settings>(Value::Object(with_entry.clone())) {
+                Ok(_) => kept = with_entry,
+                Err(err) => problems.push(format!("ignoring \"{}\": {}", key, err)),
+            }
+        }
+
+        let settings = serde_json::from_value(Value::Object(kept)).unwrap_or_default();
+        (settings, problems)
+    }
+
+    pub fn save(&self) {
+        let saved = serde_json::to_string_pretty(self)
+            .map_err(|err| err.to_string())
+            .and_then(|json| fs::write(SETTINGS_FILE, json).map_err(|err| err.to_string()));
+
+        if let Err(err) = saved {
+            eprintln!("Could not save the settings: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_wrong_entry_keeps_the_others() {
+        let (settings, problems) =
+            Settings::parse(r#"{ "theme": "classic", "palette": "sepia", "animations": false }"#);
+
+        assert_eq!(settings.theme, "classic");
+        assert_eq!(settings.palette, Palette::Standard);
+        assert!(!settings.animations);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("palette"));
+    }
+
+    #[test]
+    fn unreadable_settings_are_the_defaults() {
+        let (settings, problems) = Settings::parse("{ \"theme\": ");
+
+        assert_eq!(settings, Settings::default());
+        assert_eq!(problems.len(), 1);
+    }
+
+    #[test]
+    fn saved_settings_parse_back() {
+        let settings = Settings {
+            theme: "classic".to_string(),
+            timer: TimerDisplay::Hidden,
+            ..Settings::default()
+        };
+        let text = serde_json::to_string_pretty(&settings).unwrap();
+
+        assert_eq!(Settings::parse(&text), (settings, Vec::new()));
+    }
+}
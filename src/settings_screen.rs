@@ -0,0 +1,141 @@
+use macroquad::{
+    hash,
+    input::{get_last_key_pressed, KeyCode},
+    math::vec2,
+    ui::{root_ui, widgets, Ui},
+    window::{screen_height, screen_width},
+};
+
+use crate::{
+    bindings::{key_name, Command},
+    game_controls::{scaled_skin, PADDING, WIDTH},
+    grid::ui_scale,
+    rules::{ChordRule, WinRule},
+    settings::{Settings, TimerDisplay},
+    theme::Theme,
+};
+
+/// Edits the settings in a window over the menu. Choices are buttons that
+/// switch to the next one, like the keys for the theme and the palette.
+#[derive(Debug)]
+pub struct SettingsScreen {
+    /// command waiting for its new key
+    rebinding: Option<Command>,
+}
+
+impl Default for SettingsScreen {
+    fn default() -> Self {
+        SettingsScreen::new()
+    }
+}
+
+/// The choice after `current`, going back to the first after the last.
+fn next<T: Copy + PartialEq>(all: &[T], current: T) -> T {
+    let index = all.iter().position(|choice| *choice == current);
+    all[index.map_or(0, |index| (index + 1) % all.len())]
+}
+
+impl SettingsScreen {
+    pub fn new() -> Self {
+        SettingsScreen { rebinding: None }
+    }
+
+    /// Draws the window and changes `settings` with it, true when it is
+    /// closed with its button or `Escape`.
+    pub fn draw(&mut self, settings: &mut Settings) -> bool {
+        let mut closed = false;
+        if let Some(command) = self.rebinding {
+            match get_last_key_pressed() {
+                Some(KeyCode::Escape) => self.rebinding = None,
+                Some(key) if settings.bindings.bind(command, key) => self.rebinding = None,
+                _ => {}
+            }
+        } else if get_last_key_pressed() == Some(KeyCode::Escape) {
+            closed = true;
+        }
+
+        let scale = ui_scale();
+        let size = vec2(
+            (WIDTH * 3.0 * scale).min(screen_width()),
+            screen_height() - PADDING * scale * 2.0,
+        );
+        let pos = vec2((screen_width() - size.x) / 2.0, PADDING * scale);
+
+        let mut ui = root_ui();
+        let skin = scaled_skin(&ui, scale);
+        ui.push_skin(&skin);
+        widgets::Window::new(hash!(), pos, size)
+            .label("Settings")
+            .movable(false)
+            .ui(&mut ui, |ui| {
+                self.draw_look(ui, settings);
+                self.draw_sound(ui, settings);
+                self.draw_game(ui, settings);
+                self.draw_bindings(ui, settings);
+
+                ui.separator();
+                if ui.button(None, "Back") {
+                    closed = true;
+                }
+            });
+        ui.pop_skin();
+
+        closed
+    }
+
+    fn draw_look(&self, ui: &mut Ui, settings: &mut Settings) {
+        ui.label(None, "Look");
+        if ui.button(None, format!("theme: {}", settings.theme)) {
+            settings.theme = Theme::next(&settings.theme);
+        }
+        if ui.button(None, format!("palette: {}", settings.palette.name())) {
+            settings.palette = settings.palette.next();
+        }
+        ui.checkbox(hash!(), "animations", &mut settings.animations);
+        ui.separator();
+    }
+
+    fn draw_sound(&self, ui: &mut Ui, settings: &mut Settings) {
+        let volume = &mut settings.volume;
+        ui.label(None, "Sound");
+        ui.slider(hash!(), "master", 0.0..1.0, &mut volume.master);
+        ui.slider(hash!(), "effects", 0.0..1.0, &mut volume.effects);
+        ui.slider(hash!(), "music", 0.0..1.0, &mut volume.music);
+        ui.checkbox(hash!(), "muted", &mut volume.muted);
+        ui.separator();
+    }
+
+    fn draw_game(&self, ui: &mut Ui, settings: &mut Settings) {
+        let rules = &mut settings.rules;
+        ui.label(None, "Game");
+        if ui.button(None, format!("chord: {}", rules.chord.name())) {
+            rules.chord = next(&ChordRule::ALL, rules.chord);
+        }
+        if ui.button(None, format!("win: {}", rules.win.name())) {
+            rules.win = next(&WinRule::ALL, rules.win);
+        }
+        if ui.button(None, format!("timer: {}", settings.timer.name())) {
+            settings.timer = next(&TimerDisplay::ALL, settings.timer);
+        }
+        ui.checkbox(hash!(), "diagnostics", &mut settings.diagnostics);
+        ui.separator();
+    }
+
+    fn draw_bindings(&mut self, ui: &mut Ui, settings: &mut Settings) {
+        ui.label(None, "Keys");
+        for command in Command::ALL {
+            let key = match self.rebinding == Some(command) {
+                true => "press a key, Escape to cancel".to_string(),
+                false => key_name(settings.bindings.key(command)),
+            };
+            if ui.button(None, format!("{}: {}", command.name(), key)) {
+                self.rebinding = Some(command);
+            }
+        }
+        ui.checkbox(
+            hash!(),
+            "flag with the left button",
+            &mut settings.bindings.swap_mouse_buttons,
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Sound effects for the events of a game, and music. Without the `sound`
+//! feature macroquad has no audio backend, and nothing is played.
+
+use std::{collections::HashMap, fmt, path::Path};
+
+use macroquad::audio::{
+    load_sound, play_sound, set_sound_volume, stop_sound, PlaySoundParams, Sound,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::events::GameEvent;
+
+pub const DEFAULT_SOUNDS_DIR: &str = "./sounds";
+const MUSIC_FILE: &str = "music.wav";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SoundEffect {
+    Reveal,
+    FloodFill,
+    Flag,
+    Unflag,
+    Chord,
+    Explosion,
+    Win,
+}
+
+impl SoundEffect {
+    pub const ALL: [SoundEffect; 7] = [
+        SoundEffect::Reveal,
+        SoundEffect::FloodFill,
+        SoundEffect::Flag,
+        SoundEffect::Unflag,
+        SoundEffect::Chord,
+        SoundEffect::Explosion,
+        SoundEffect::Win,
+    ];
+
+    fn file_name(&self) -> &'static str {
+        match self {
+            SoundEffect::Reveal => "reveal.wav",
+            SoundEffect::FloodFill => "flood_fill.wav",
+            SoundEffect::Flag => "flag.wav",
+            SoundEffect::Unflag => "unflag.wav",
+            SoundEffect::Chord => "chord.wav",
+            SoundEffect::Explosion => "explosion.wav",
+            SoundEffect::Win => "win.wav",
+        }
+    }
+
+    pub fn for_event(event: &GameEvent) -> Option<SoundEffect> {
+        match event {
+            GameEvent::Revealed { mine: true, .. } => Some(SoundEffect::Explosion),
+            GameEvent::Revealed { tiles, .. } if *tiles > 1 => Some(SoundEffect::FloodFill),
+            GameEvent::Revealed { .. } => Some(SoundEffect::Reveal),
+            GameEvent::Chorded { .. } => Some(SoundEffect::Chord),
+            GameEvent::Flagged { .. } => Some(SoundEffect::Flag),
+            GameEvent::Unflagged { .. } => Some(SoundEffect::Unflag),
+            GameEvent::Won { .. } => Some(SoundEffect::Win),
+            _ => None,
+        }
+    }
+}
+
+/// Volumes from 0 to 1, the master volume scales the other two.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Volume {
+    pub master: f32,
+    pub effects: f32,
+    pub music: f32,
+    pub muted: bool,
+}
+
+impl Default for Volume {
+    fn default() -> Self {
+        Volume {
+            master: 0.8,
+            effects: 1.0,
+            music: 0.4,
+            muted: false,
+        }
+    }
+}
+
+impl Volume {
+    fn level(&self, volume: f32) -> f32 {
+        match self.muted {
+            true => 0.0,
+            false => (self.master * volume).clamp(0.0, 1.0),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct SoundPack {
+    effects: HashMap<SoundEffect, Sound>,
+    music: Option<Sound>,
+}
+
+impl fmt::Debug for SoundPack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SoundPack")
+            .field("effects", &self.effects.keys().collect::<Vec<_>>())
+            .field("music", &self.music.is_some())
+            .finish()
+    }
+}
+
+impl SoundPack {
+    /// Loads the sounds in the directory, taking the missing ones from
+    /// `fallback`.
+    pub async fn load(dir: &Path, fallback: Option<&SoundPack>) -> Self {
+        let mut pack = fallback.cloned().unwrap_or_default();
+        if !cfg!(feature = "sound") {
+            return pack;
+        }
+
+        for effect in SoundEffect::ALL {
+            if let Some(sound) = SoundPack::load_file(&dir.join(effect.file_name())).await {
+                pack.effects.insert(effect, sound);
+            }
+        }
+
+        if let Some(music) = SoundPack::load_file(&dir.join(MUSIC_FILE)).await {
+            pack.music = Some(music);
+        }
+
+        pack
+    }
+
+    async fn load_file(path: &Path) -> Option<Sound> {
+        if !path.is_file() {
+            return None;
+        }
+
+        match load_sound(&path.to_string_lossy()).await {
+            Ok(sound) => Some(sound),
+            Err(err) => {
+                eprintln!("Could not load the sound {}: {}", path.display(), err);
+                None
+            }
+        }
+    }
+
+    pub fn play(&self, effect: SoundEffect, volume: &Volume) {
+        let level = volume.level(volume.effects);
+        if let (Some(sound), true) = (self.effects.get(&effect), level > 0.0) {
+            play_sound(
+                sound,
+                PlaySoundParams {
+                    looped: false,
+                    volume: level,
+                },
+            );
+        }
+    }
+
+    /// Loops the music, at no volume while muted so unmuting resumes it.
+    pub fn start_music(&self, volume: &Volume) {
+        if let Some(music) = &self.music {
+            play_sound(
+                music,
+                PlaySoundParams {
+                    looped: true,
+                    volume: volume.level(volume.music),
+                },
+            );
+        }
+    }
+
+    pub fn stop_music(&self) {
+        if let Some(music) = &self.music {
+            stop_sound(music);
+        }
+    }
+
+    pub fn set_music_volume(&self, volume: &Volume) {
+        if let Some(music) = &self.music {
+            set_sound_volume(music, volume.level(volume.music));
+        }
+    }
+}
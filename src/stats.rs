@@ -0,0 +1,51 @@
+use std::fs;
+
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{difficulty::Difficulty, utils::load_json};
+
+const STATS_FILE: &str = "./stats.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub played_at: NaiveDateTime,
+    pub level: String,
+    pub seed: u64,
+    pub won: bool,
+    pub seconds: i64,
+    /// only generated boards are rated
+    pub difficulty: Option<Difficulty>,
+}
+
+/// Every finished game started from a level.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Stats {
+    pub games: Vec<GameRecord>,
+}
+
+impl Stats {
+    pub fn load() -> Self {
+        load_json(STATS_FILE)
+    }
+
+    pub fn record(level: &str, seed: u64, won: bool, seconds: i64, difficulty: Option<Difficulty>) {
+        let mut stats = Stats::load();
+        stats.games.push(GameRecord {
+            played_at: Utc::now().naive_utc(),
+            level: level.to_string(),
+            seed,
+            won,
+            seconds,
+            difficulty,
+        });
+
+        let saved = serde_json::to_string_pretty(&stats)
+            .map_err(|err| err.to_string())
+            .and_then(|json| fs::write(STATS_FILE, json).map_err(|err| err.to_string()));
+
+        if let Err(err) = saved {
+            eprintln!("Could not save the stats: {}", err);
+        }
+    }
+}
@@ -0,0 +1,332 @@
+//! Themes are directories in `themes` with a `theme.json` manifest:
+//!
+//! ```json
+//! {
+//!   "colors": { "background": "#c0c0c0", "hidden": "#c0c0c0", "revealed": "#c0c0c0",
+//!               "mine_background": "#ff0000", "flag_background": "#c0c0c0", "text": "#000000" },
+//!   "numbers": ["#0000ff", "#008000", "#ff0000"],
+//!   "textures": { "hidden": "hidden.png", "revealed": "revealed.png", "flag": "flag.png",
+//!                 "mine": "mine.png", "wrong_flag": "wrong_flag.png" },
+//!   "font": "font.ttf",
+//!   "sounds": "sounds"
+//! }
+//! ```
+//!
+//! Every entry is optional, whatever is missing or fails to load is taken
+//! from the built-in default theme.
+
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use macroquad::{
+    color::{Color, BLACK, BLUE, GREEN, LIGHTGRAY, RED, SKYBLUE, WHITE, YELLOW},
+    text::{load_ttf_font, Font},
+    texture::Texture2D,
+};
+use serde::Deserialize;
+
+use crate::{
+    game_textures::GameTextures,
+    settings::Settings,
+    sound::{SoundPack, DEFAULT_SOUNDS_DIR},
+};
+
+const THEMES_DIR: &str = "./themes";
+const MANIFEST_FILE: &str = "theme.json";
+pub const DEFAULT_THEME: &str = "default";
+const DEFAULT_FONT: &str = "fonts/DejaVuSans-Bold.ttf";
+
+/// The numbers of the original game: blue 1, green 2, red 3, navy 4, maroon 5,
+/// teal 6, black 7 and gray 8.
+const CLASSIC_NUMBERS: [Color; 8] = [
+    Color::from_hex(0x0000ff),
+    Color::from_hex(0x008000),
+    Color::from_hex(0xff0000),
+    Color::from_hex(0x000080),
+    Color::from_hex(0x800000),
+    Color::from_hex(0x008080),
+    Color::from_hex(0x000000),
+    Color::from_hex(0x808080),
+];
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ThemeManifest {
+    colors: ColorsManifest,
+    numbers: Vec<String>,
+    textures: TexturesManifest,
+    font: Option<String>,
+    /// directory of sound files named like those in `sounds`
+    sounds: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ColorsManifest {
+    background: Option<String>,
+    hidden: Option<String>,
+    revealed: Option<String>,
+    mine_background: Option<String>,
+    flag_background: Option<String>,
+    text: Option<String>,
+    win: Option<String>,
+    loss: Option<String>,
+    hint: Option<String>,
+    partial: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct TexturesManifest {
+    hidden: Option<String>,
+    revealed: Option<String>,
+    flag: Option<String>,
+    mine: Option<String>,
+    wrong_flag: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThemeColors {
+    pub background: Color,
+    pub hidden: Color,
+    pub revealed: Color,
+    pub mine_background: Color,
+    pub flag_background: Color,
+    pub text: Color,
+    pub win: Color,
+    pub loss: Color,
+    /// the tiles a hint is drawn from and the text of hints and turns
+    pub hint: Color,
+    /// done, but not cleanly, like a puzzle solved after mistakes
+    pub partial: Color,
+}
+
+impl Default for ThemeColors {
+    fn default() -> Self {
+        ThemeColors {
+            background: WHITE,
+            hidden: SKYBLUE,
+            revealed: LIGHTGRAY,
+            mine_background: RED,
+            flag_background: SKYBLUE,
+            text: BLACK,
+            win: GREEN,
+            loss: RED,
+            hint: BLUE,
+            partial: YELLOW,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Theme {
+    pub name: String,
+    pub colors: ThemeColors,
+    /// colour of each number from 1 up, the text colour past the last one
+    pub numbers: Vec<Color>,
+    pub textures: GameTextures,
+    pub font: Option<Font>,
+    pub sounds: SoundPack,
+    /// patterns and outlines that tell tiles apart without their colours
+    pub shape_cues: bool,
+}
+
+impl fmt::Debug for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Theme")
+            .field("name", &self.name)
+            .field("colors", &self.colors)
+            .field("numbers", &self.numbers)
+            .field("textures", &self.textures)
+            .field("sounds", &self.sounds)
+            .field("shape_cues", &self.shape_cues)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Parses `#rrggbb` or `#rrggbbaa`.
+pub fn parse_color(text: &str) -> Option<Color> {
+    let hex = text.strip_prefix('#')?;
+    if !hex.is_ascii() || (hex.len() != 6 && hex.len() != 8) {
+        return None;
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let alpha = match hex.len() {
+        8 => channel(6)?,
+        _ => 255,
+    };
+
+    Some(Color::from_rgba(
+        channel(0)?,
+        channel(2)?,
+        channel(4)?,
+        alpha,
+    ))
+}
+
+impl Theme {
+    pub async fn default_theme() -> Self {
+        Theme {
+            name: DEFAULT_THEME.to_string(),
+            colors: ThemeColors::default(),
+            numbers: CLASSIC_NUMBERS.to_vec(),
+            textures: GameTextures::new().await,
+            font: Theme::load_font(Path::new(DEFAULT_FONT)).await,
+            sounds: SoundPack::load(Path::new(DEFAULT_SOUNDS_DIR), None).await,
+            shape_cues: false,
+        }
+    }
+
+    /// Loads the theme from its directory, falling back to `default` for
+    /// anything missing. The default is loaded once by the caller, so
+    /// switching themes doesn't load its textures, font and sounds again.
+    pub async fn load(name: &str, default: &Theme) -> Self {
+        let default = default.clone();
+        if name == DEFAULT_THEME {
+            return default;
+        }
+
+        let dir = Path::new(THEMES_DIR).join(name);
+        let manifest: ThemeManifest = match fs::read_to_string(dir.join(MANIFEST_FILE))
+            .map_err(|err| err.to_string())
+            .and_then(|text| serde_json::from_str(&text).map_err(|err| err.to_string()))
+        {
+            Ok(manifest) => manifest,
+            Err(err) => {
+                eprintln!("Could not load the theme {}: {}", name, err);
+                return default;
+            }
+        };
+
+        let color = |value: &Option<String>, fallback: Color| match value {
+            Some(text) => parse_color(text).unwrap_or_else(|| {
+                eprintln!("Theme {}: '{}' is not a colour", name, text);
+                fallback
+            }),
+            None => fallback,
+        };
+
+        let colors = ThemeColors {
+            background: color(&manifest.colors.background, default.colors.background),
+            hidden: color(&manifest.colors.hidden, default.colors.hidden),
+            revealed: color(&manifest.colors.revealed, default.colors.revealed),
+            mine_background: color(
+                &manifest.colors.mine_background,
+                default.colors.mine_background,
+            ),
+            flag_background: color(
+                &manifest.colors.flag_background,
+                default.colors.flag_background,
+            ),
+            text: color(&manifest.colors.text, default.colors.text),
+            win: color(&manifest.colors.win, default.colors.win),
+            loss: color(&manifest.colors.loss, default.colors.loss),
+            hint: color(&manifest.colors.hint, default.colors.hint),
+            partial: color(&manifest.colors.partial, default.colors.partial),
+        };
+
+        let numbers = match manifest.numbers.is_empty() {
+            true => default.numbers,
+            false => manifest
+                .numbers
+                .iter()
+                .map(|number| color(&Some(number.clone()), colors.text))
+                .collect(),
+        };
+
+        let files = &manifest.textures;
+        let textures = GameTextures {
+            hidden: Theme::load_texture(&dir, &files.hidden).await,
+            revealed: Theme::load_texture(&dir, &files.revealed).await,
+            flag: Theme::load_texture(&dir, &files.flag)
+                .await
+                .or(default.textures.flag),
+            mine: Theme::load_texture(&dir, &files.mine)
+                .await
+                .or(default.textures.mine),
+            wrong_flag: Theme::load_texture(&dir, &files.wrong_flag).await,
+        };
+
+        let font = match &manifest.font {
+            Some(file) => Theme::load_font(&dir.join(file)).await.or(default.font),
+            None => default.font,
+        };
+
+        let sounds = match &manifest.sounds {
+            Some(sounds_dir) => SoundPack::load(&dir.join(sounds_dir), Some(&default.sounds)).await,
+            None => default.sounds,
+        };
+
+        Theme {
+            name: name.to_string(),
+            colors,
+            numbers,
+            textures,
+            font,
+            sounds,
+            shape_cues: false,
+        }
+    }
+
+    /// The theme and palette picked in the settings.
+    pub async fn from_settings(settings: &Settings, default: &Theme) -> Self {
+        let mut theme = Theme::load(&settings.theme, default).await;
+        settings.palette.apply(&mut theme);
+
+        theme
+    }
+
+    async fn load_texture(dir: &Path, file: &Option<String>) -> Option<Texture2D> {
+        GameTextures::load(&dir.join(file.as_ref()?)).await
+    }
+
+    async fn load_font(path: &Path) -> Option<Font> {
+        match load_ttf_font(&path.to_string_lossy()).await {
+            Ok(font) => Some(font),
+            Err(err) => {
+                eprintln!("Could not load the font {}: {}", path.display(), err);
+                None
+            }
+        }
+    }
+
+    /// The default theme and the theme directories, in name order.
+    pub fn available() -> Vec<String> {
+        let mut themes: Vec<String> = fs::read_dir(THEMES_DIR)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .filter(|path| path.join(MANIFEST_FILE).is_file())
+                    .filter_map(|path: PathBuf| Some(path.file_name()?.to_str()?.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        themes.sort();
+        themes.insert(0, DEFAULT_THEME.to_string());
+
+        themes
+    }
+
+    /// The theme after `name` in the available ones.
+    pub fn next(name: &str) -> String {
+        let themes = Theme::available();
+        let index = themes
+            .iter()
+            .position(|theme| theme == name)
+            .map_or(0, |index| (index + 1) % themes.len());
+
+        themes[index].clone()
+    }
+
+    /// Colour of a number of mines around a tile.
+    pub fn number_color(&self, number: i32) -> Color {
+        self.numbers
+            .get((number - 1).max(0) as usize)
+            .copied()
+            .unwrap_or(self.colors.text)
+    }
+}
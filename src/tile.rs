@@ -1,23 +1,18 @@
 use macroquad::{
+    color::{Color, WHITE},
     math::Vec2,
-    shapes::draw_rectangle,
-    text::draw_text,
-    texture::{draw_texture_ex, DrawTextureParams},
+    shapes::{draw_line, draw_rectangle, draw_rectangle_lines},
+    text::{draw_text_ex, measure_text, TextParams},
+    texture::{draw_texture_ex, DrawTextureParams, Texture2D},
 };
 
-use crate::{game_textures::GameTextures, vector2::Vector2};
+use crate::{theme::Theme, vector2::Vector2};
 
 mod consts {
-    use macroquad::color::{Color, BLACK, LIGHTGRAY, RED, SKYBLUE, WHITE};
-
-    pub const HIDDEN_COLOR: Color = SKYBLUE;
-    pub const MINE_COLOR: Color = WHITE;
-    pub const MINE_BACKGROUND_COLOR: Color = RED;
-    pub const FLAG_BACKGROUND_COLOR: Color = HIDDEN_COLOR;
-    pub const NO_MINE_COLOR: Color = LIGHTGRAY;
-    pub const TEXT_COLOR: Color = BLACK;
-
-    pub const DIGITS: &[&str] = &["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+    pub const DIGITS: &[&str] = &[
+        "0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12", "13", "14", "15", "16",
+        "17", "18", "19", "20", "21", "22", "23", "24", "25", "26",
+    ];
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -27,7 +22,7 @@ pub enum TileState {
     Revealed,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Tile {
     pub has_mine: bool,
     pub state: TileState,
@@ -49,42 +44,126 @@ impl Tile {
         self.num_mines_around = num_mines_around;
     }
 
-    pub fn draw(&self, pos: Vector2<f32>, size: f32, textures: &GameTextures) {
-        let color = match self.state {
-            TileState::Hidden => consts::HIDDEN_COLOR,
-            TileState::Flagged => consts::FLAG_BACKGROUND_COLOR,
-            TileState::Revealed if self.has_mine => consts::MINE_BACKGROUND_COLOR,
-            _ => consts::NO_MINE_COLOR,
+    /// Draws the tile, showing wrong flags once the game is lost.
+    pub fn draw(
+        &self,
+        pos: Vector2<f32>,
+        size: f32,
+        theme: &Theme,
+        outline: Option<Color>,
+        game_over: bool,
+    ) {
+        let textures = &theme.textures;
+        let (background, color) = match self.state {
+            TileState::Hidden => (&textures.hidden, theme.colors.hidden),
+            TileState::Flagged => (&textures.hidden, theme.colors.flag_background),
+            TileState::Revealed if self.has_mine => (&None, theme.colors.mine_background),
+            _ => (&textures.revealed, theme.colors.revealed),
         };
 
-        draw_rectangle(pos.x, pos.y, size, size, color);
+        match background {
+            Some(texture) => Tile::draw_texture(texture, pos, size),
+            None => draw_rectangle(pos.x, pos.y, size, size, color),
+        }
+
+        if theme.shape_cues && self.state == TileState::Revealed && self.has_mine {
+            Tile::draw_hatching(pos, size, theme.colors.text);
+        }
 
+        let wrong_flag = game_over && self.state == TileState::Flagged && !self.has_mine;
         if let Some(texture) = match self.state {
-            TileState::Flagged => Some(&textures.flag),
-            TileState::Revealed if self.has_mine => Some(&textures.bomb),
+            TileState::Flagged if wrong_flag => {
+                textures.wrong_flag.as_ref().or(textures.flag.as_ref())
+            }
+            TileState::Flagged => textures.flag.as_ref(),
+            TileState::Revealed if self.has_mine => textures.mine.as_ref(),
             _ => None,
         } {
-            draw_texture_ex(
-                texture,
+            Tile::draw_texture(texture, pos, size);
+        }
+
+        if theme.shape_cues && self.state == TileState::Flagged {
+            let thickness = (size / 8.0).max(2.0);
+            draw_rectangle_lines(
+                pos.x + thickness,
+                pos.y + thickness,
+                size - thickness * 2.0,
+                size - thickness * 2.0,
+                thickness,
+                theme.colors.text,
+            );
+        }
+
+        if wrong_flag && textures.wrong_flag.is_none() {
+            let thickness = (size / 10.0).max(2.0);
+            draw_line(
                 pos.x,
                 pos.y,
-                consts::MINE_COLOR,
-                Tile::get_texture_params(size),
+                pos.x + size,
+                pos.y + size,
+                thickness,
+                theme.colors.loss,
+            );
+            draw_line(
+                pos.x + size,
+                pos.y,
+                pos.x,
+                pos.y + size,
+                thickness,
+                theme.colors.loss,
             );
         }
 
         if self.num_mines_around > 0 && self.state == TileState::Revealed && !self.has_mine {
-            let text_pos = pos
-                .add_val(size / 2.0)
-                .add(Vector2::new(-size / 5.0, size / 5.0));
-            draw_text(
-                consts::DIGITS[self.num_mines_around as usize],
-                text_pos.x,
-                text_pos.y,
-                size,
-                consts::TEXT_COLOR,
+            // layered boards can have two digit counts, which need a smaller font to fit
+            let text = consts::DIGITS[self.num_mines_around as usize];
+            let font_size = match text.len() {
+                1 => size * 0.8,
+                _ => size * 0.55,
+            } as u16;
+            let font = theme.font.as_ref();
+
+            // centred on the glyphs as measured, which scale differently from the font size
+            let dimensions = measure_text(text, font, font_size, 1.0);
+            draw_text_ex(
+                text,
+                pos.x + (size - dimensions.width) / 2.0,
+                pos.y + (size - dimensions.height) / 2.0 + dimensions.offset_y,
+                TextParams {
+                    font,
+                    font_size,
+                    color: theme.number_color(self.num_mines_around),
+                    ..Default::default()
+                },
             );
         }
+
+        if let Some(color) = outline {
+            draw_rectangle_lines(pos.x, pos.y, size, size, (size / 10.0).max(2.0), color);
+        }
+    }
+
+    /// Diagonal stripes across the tile.
+    fn draw_hatching(pos: Vector2<f32>, size: f32, color: Color) {
+        let spacing = (size / 4.0).max(4.0);
+        let mut offset = spacing;
+        while offset < size * 2.0 {
+            // both ends are on the line x + y = offset, cut to the tile
+            let cut = offset.min(size);
+            draw_line(
+                pos.x + cut,
+                pos.y + offset - cut,
+                pos.x + offset - cut,
+                pos.y + cut,
+                (size / 16.0).max(1.0),
+                color,
+            );
+            offset += spacing;
+        }
+    }
+
+    fn draw_texture(texture: &Texture2D, pos: Vector2<f32>, size: f32) {
+        draw_texture_ex(texture, pos.x, pos.y, WHITE, Tile::get_texture_params(size));
     }
 
     fn get_texture_params(size: f32) -> DrawTextureParams {
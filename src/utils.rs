@@ -1,12 +1,108 @@
-use chrono::offset::Utc;
+use std::{collections::HashMap, fs, io::ErrorKind};
+
+use chrono::{offset::Utc, NaiveDate};
+use serde::de::DeserializeOwned;
 
 pub fn current_time_seconds() -> i64 {
     Utc::now().timestamp()
 }
 
+pub fn current_date() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
+pub fn current_time_millis() -> i64 {
+    Utc::now().timestamp_millis()
+}
+
+/// Current time formatted to be safe in file names.
+pub fn current_time_string() -> String {
+    Utc::now().format("%Y%m%d-%H%M%S").to_string()
+}
+
 pub fn get_time_diff(start: i64, end: i64) -> (i32, i32) {
     let diff = end - start;
     let minutes = (diff / 60) as i32;
     let seconds = (diff % 60) as i32;
     (minutes, seconds)
 }
+
+/// Splits command line arguments into `--name value` options and the
+/// remaining positional arguments.
+pub fn parse_args(args: impl Iterator<Item = String>) -> (HashMap<String, String>, Vec<String>) {
+    let mut options = HashMap::new();
+    let mut positional = Vec::new();
+    let mut args = args.peekable();
+
+    while let Some(arg) = args.next() {
+        match arg.strip_prefix("--") {
+            Some(name) => {
+                let value = args.next_if(|value| !value.starts_with("--"));
+                options.insert(name.to_string(), value.unwrap_or_default());
+            }
+            None => positional.push(arg),
+        }
+    }
+
+    (options, positional)
+}
+
+/// Reads a JSON file the game keeps its data in, the default when there is
+/// none yet. A file that can't be read is reported and copied aside first,
+/// as the next save replaces it.
+pub fn load_json<T: DeserializeOwned + Default>(path: &str) -> T {
+    let parsed = fs::read_to_string(path)
+        .map_err(|err| match err.kind() {
+            ErrorKind::NotFound => None,
+            _ => Some(err.to_string()),
+        })
+        .and_then(|text| serde_json::from_str(&text).map_err(|err| Some(err.to_string())));
+
+    match parsed {
+        Ok(data) => data,
+        Err(None) => T::default(),
+        Err(Some(err)) => {
+            eprintln!("Could not read {}: {}", path, err);
+            let backup = format!("{}.{}.bak", path, current_time_string());
+            match fs::copy(path, &backup) {
+                Ok(_) => eprintln!("Starting again, the old file is kept as {}", backup),
+                Err(err) => eprintln!("Starting again, could not keep the old file: {}", err),
+            }
+            T::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, env};
+
+    use super::*;
+
+    #[test]
+    fn unreadable_json_is_kept_aside() {
+        let dir = env::temp_dir().join(format!("minesweeper-load-json-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("data.json").to_string_lossy().to_string();
+
+        let missing: BTreeMap<String, i32> = load_json(&path);
+        assert!(missing.is_empty());
+
+        fs::write(&path, r#"{ "a": 1 }"#).unwrap();
+        let read: BTreeMap<String, i32> = load_json(&path);
+        assert_eq!(read, BTreeMap::from([("a".to_string(), 1)]));
+
+        fs::write(&path, r#"{ "a": "#).unwrap();
+        let corrupt: BTreeMap<String, i32> = load_json(&path);
+        assert!(corrupt.is_empty());
+        let backups: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".bak"))
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read_to_string(backups[0].path()).unwrap(), r#"{ "a": "#);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
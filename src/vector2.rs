@@ -81,6 +81,17 @@ impl<T> Vector2<T> {
             self.y
         }
     }
+
+    pub fn max_component(&self) -> T
+    where
+        T: PartialOrd + Copy,
+    {
+        if self.x > self.y {
+            self.x
+        } else {
+            self.y
+        }
+    }
 }
 
 impl From<Vector2<i32>> for Vector2<f32> {